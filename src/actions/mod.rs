@@ -24,6 +24,14 @@ impl Plugin for ActionsPlugin {
 #[derive(Default)]
 pub struct Actions {
     pub player_movement: Option<Vec2>,
+    // Tile picked with Walk here, dropped as soon as the keyboard takes over
+    pub walk_target: Option<Vec2>,
+}
+
+impl Actions {
+    pub fn is_moving(&self) -> bool {
+        self.player_movement.is_some() || self.walk_target.is_some()
+    }
 }
 
 fn set_movement_actions(mut actions: ResMut<Actions>, keyboard_input: Res<Input<KeyCode>>) {
@@ -36,6 +44,7 @@ fn set_movement_actions(mut actions: ResMut<Actions>, keyboard_input: Res<Input<
 
     if player_movement != Vec2::ZERO {
         actions.player_movement = Some(player_movement.normalize());
+        actions.walk_target = None;
     } else {
         actions.player_movement = None;
     }
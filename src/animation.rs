@@ -0,0 +1,81 @@
+use bevy::gltf::Gltf;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+pub struct WalkAnimationPlugin;
+
+// The animation player sits on the glTF scene's root, a few levels below the entity that walks
+const MAX_DEPTH: usize = 3;
+
+// Something that walks, animated with the "walk" and "idle" clips of its glTF file if it has them
+#[derive(Component)]
+pub struct Walker {
+    pub gltf: Handle<Gltf>,
+    pub moving: bool,
+}
+
+impl Walker {
+    pub fn new(gltf: Handle<Gltf>) -> Walker {
+        Walker {
+            gltf,
+            moving: false,
+        }
+    }
+}
+
+impl Plugin for WalkAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(play_walk_animations);
+    }
+}
+
+fn find_clip(gltf: &Gltf, name: &str) -> Option<Handle<AnimationClip>> {
+    gltf.named_animations
+        .iter()
+        .find(|(clip, _)| clip.eq_ignore_ascii_case(name))
+        .map(|(_, handle)| handle.clone())
+}
+
+fn play_walk_animations(
+    gltfs: Res<Assets<Gltf>>,
+    walkers: Query<&Walker>,
+    parents: Query<&Parent>,
+    mut animation_players: Query<(Entity, &mut AnimationPlayer)>,
+    mut playing: Local<HashMap<Entity, bool>>,
+) {
+    playing.retain(|entity, _| animation_players.contains(*entity));
+
+    for (entity, mut animation_player) in animation_players.iter_mut() {
+        let mut ancestor = entity;
+        let mut walker = None;
+        for _ in 0..MAX_DEPTH {
+            ancestor = match parents.get(ancestor) {
+                Ok(parent) => parent.get(),
+                Err(_) => break,
+            };
+            if let Ok(found) = walkers.get(ancestor) {
+                walker = Some(found);
+                break;
+            }
+        }
+        let walker = match walker {
+            Some(walker) => walker,
+            None => continue,
+        };
+
+        // Only switch clips when the walker starts or stops, so they don't restart every frame
+        if playing.get(&entity) == Some(&walker.moving) {
+            continue;
+        }
+        let name = if walker.moving { "walk" } else { "idle" };
+        let clip = match gltfs
+            .get(&walker.gltf)
+            .and_then(|gltf| find_clip(gltf, name))
+        {
+            Some(clip) => clip,
+            None => continue,
+        };
+        animation_player.play(clip).repeat();
+        playing.insert(entity, walker.moving);
+    }
+}
@@ -0,0 +1,251 @@
+use crate::blood_spawn::{BloodSpawn, BLOOD_SPAWN_SIZE};
+use crate::camera;
+use crate::clickbox::Clickbox;
+use crate::matomenos::{stacks, Matomenos};
+use crate::overlay::{painter, OverlaySettings, Projector};
+use crate::sim::FrozenState;
+use crate::tick::GameTick;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+pub struct BillboardPlugin;
+
+const HEALTH_BAR_SIZE: egui::Vec2 = egui::vec2(30.0, 5.0);
+const HITSPLAT_RADIUS: f32 = 9.0;
+
+// How long a hitsplat stays up, two ticks like the client
+const HITSPLAT_SECONDS: f32 = 2.0 * 0.6;
+
+const HEALTH_COLOR: egui::Color32 = egui::Color32::from_rgb(0, 255, 0);
+const MISSING_HEALTH_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 0, 0);
+const FROZEN_COLOR: egui::Color32 = egui::Color32::from_rgb(120, 200, 255);
+const IMMUNE_COLOR: egui::Color32 = egui::Color32::from_rgb(180, 180, 180);
+const DAMAGE_COLOR: egui::Color32 = egui::Color32::from_rgb(180, 20, 20);
+const SPLASH_COLOR: egui::Color32 = egui::Color32::from_rgb(40, 80, 200);
+const STACK_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 140, 0);
+
+struct Hitsplat {
+    // Zero for a splash
+    damage: u32,
+    timer: Timer,
+}
+
+// Recent hits on anything that can take damage
+#[derive(Component)]
+pub struct Hitsplats {
+    hitsplats: Vec<Hitsplat>,
+    // Where to draw them, above the entity's origin
+    height: f32,
+}
+
+impl Hitsplats {
+    pub fn new(height: f32) -> Hitsplats {
+        Hitsplats {
+            hitsplats: Vec::new(),
+            height,
+        }
+    }
+
+    pub fn add(&mut self, damage: u32) {
+        self.hitsplats.push(Hitsplat {
+            damage,
+            timer: Timer::from_seconds(HITSPLAT_SECONDS, false),
+        });
+    }
+}
+
+impl Plugin for BillboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Spawned)
+                .with_system(draw_billboards)
+                .with_system(draw_hitsplats)
+                .with_system(age_hitsplats),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::Paused)
+                .with_system(draw_billboards)
+                .with_system(draw_hitsplats),
+        );
+    }
+}
+
+fn age_hitsplats(time: Res<Time>, tick: Res<GameTick>, mut query: Query<&mut Hitsplats>) {
+    for mut hitsplats in query.iter_mut() {
+        for hitsplat in hitsplats.hitsplats.iter_mut() {
+            hitsplat.timer.tick(tick.scale(time.delta()));
+        }
+        hitsplats
+            .hitsplats
+            .retain(|hitsplat| !hitsplat.timer.finished());
+    }
+}
+
+fn draw_health_bar(painter: &egui::Painter, center: egui::Pos2, hitpoints: u32, max: u32) {
+    let bar = egui::Rect::from_center_size(center, HEALTH_BAR_SIZE);
+    let mut remaining = bar;
+    remaining.set_width(bar.width() * hitpoints as f32 / max as f32);
+    painter.rect_filled(bar, 0.0, MISSING_HEALTH_COLOR);
+    painter.rect_filled(remaining, 0.0, HEALTH_COLOR);
+}
+
+// Health bar, timers and label above each nylo, stack counts, and health bars above blood spawns
+fn draw_billboards(
+    mut egui_context: ResMut<EguiContext>,
+    settings: Res<OverlaySettings>,
+    clickbox: Res<Clickbox>,
+    nylos_query: Query<(Entity, &Transform, &Matomenos)>,
+    blood_spawns_query: Query<(&Transform, &BloodSpawn)>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<camera::Camera>>,
+) {
+    let projector = match camera_query
+        .get_single()
+        .ok()
+        .and_then(|(camera, transform)| Projector::new(camera, transform))
+    {
+        Some(projector) => projector,
+        None => return,
+    };
+    let painter = painter(egui_context.ctx_mut(), "billboards");
+    let small = egui::FontId::proportional(12.0);
+
+    for (_, transform, nylo) in nylos_query.iter() {
+        let head = Vec3::Y * (clickbox.hull.top() + 0.25);
+        let drawn = transform.translation + nylo.lag() + nylo.stack_offset();
+        let head = match projector.project(drawn + head) {
+            Some(head) => head,
+            None => continue,
+        };
+
+        if settings.health_bars {
+            draw_health_bar(&painter, head, nylo.hitpoints(), nylo.max_hitpoints());
+        }
+
+        if settings.spawn_labels {
+            painter.text(
+                head - egui::vec2(0.0, HEALTH_BAR_SIZE.y + 2.0),
+                egui::Align2::CENTER_BOTTOM,
+                nylo.spawn().label(),
+                small.clone(),
+                egui::Color32::YELLOW,
+            );
+        }
+
+        if settings.freeze_timers {
+            let timer = match nylo.frozen() {
+                FrozenState::Frozen(ticks) => Some((format!("frozen {}", ticks), FROZEN_COLOR)),
+                FrozenState::Immune(ticks) => Some((format!("immune {}", ticks), IMMUNE_COLOR)),
+                FrozenState::NotFrozen | FrozenState::ShouldFreeze => None,
+            };
+            if let Some((text, color)) = timer {
+                painter.text(
+                    head + egui::vec2(0.0, HEALTH_BAR_SIZE.y + 2.0),
+                    egui::Align2::CENTER_TOP,
+                    text,
+                    small.clone(),
+                    color,
+                );
+            }
+        }
+    }
+
+    if settings.stack_badges {
+        let nylos = nylos_query
+            .iter()
+            .map(|(entity, transform, _)| (entity, transform));
+        for ((x, z), entities) in stacks(nylos) {
+            if entities.len() < 2 {
+                continue;
+            }
+            // The badge walks with the models, which may be coming from different tiles
+            let lag = entities
+                .iter()
+                .filter_map(|entity| nylos_query.get(*entity).ok())
+                .fold(Vec3::ZERO, |sum, (_, _, nylo)| sum + nylo.lag())
+                / entities.len() as f32;
+            let top = Vec3::new(x, 0.0, z) + lag + Vec3::Y * (clickbox.hull.top() + 0.25);
+            if let Some(top) = projector.project(top) {
+                let badge = top + egui::vec2(HEALTH_BAR_SIZE.x / 2.0 + 10.0, 0.0);
+                painter.circle_filled(badge, 8.0, STACK_COLOR);
+                painter.text(
+                    badge,
+                    egui::Align2::CENTER_CENTER,
+                    entities.len().to_string(),
+                    small.clone(),
+                    egui::Color32::BLACK,
+                );
+            }
+        }
+    }
+
+    if settings.health_bars {
+        for (transform, blood_spawn) in blood_spawns_query.iter() {
+            let head = transform.translation + Vec3::Y * BLOOD_SPAWN_SIZE;
+            if let Some(head) = projector.project(head) {
+                draw_health_bar(
+                    &painter,
+                    head,
+                    blood_spawn.hitpoints(),
+                    blood_spawn.max_hitpoints(),
+                );
+            }
+        }
+    }
+}
+
+fn draw_hitsplats(
+    mut egui_context: ResMut<EguiContext>,
+    settings: Res<OverlaySettings>,
+    hitsplats_query: Query<(&Transform, &Hitsplats, Option<&Matomenos>)>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<camera::Camera>>,
+) {
+    if !settings.hitsplats {
+        return;
+    }
+    let projector = match camera_query
+        .get_single()
+        .ok()
+        .and_then(|(camera, transform)| Projector::new(camera, transform))
+    {
+        Some(projector) => projector,
+        None => return,
+    };
+    let painter = painter(egui_context.ctx_mut(), "hitsplats");
+
+    for (transform, hitsplats, nylo) in hitsplats_query.iter() {
+        // Nylo hitsplats go on the model, wherever it's drawn
+        let drawn = transform.translation
+            + nylo.map_or(Vec3::ZERO, |nylo| nylo.lag() + nylo.stack_offset());
+        let body = match projector.project(drawn + Vec3::Y * hitsplats.height) {
+            Some(body) => body,
+            None => continue,
+        };
+
+        // Spread several hitsplats side by side, newest in the middle
+        for (i, hitsplat) in hitsplats.hitsplats.iter().rev().enumerate() {
+            let side = if i % 2 == 0 { 1.0 } else { -1.0 };
+            let offset = side * i.div_ceil(2) as f32 * HITSPLAT_RADIUS * 2.2;
+            let center = body + egui::vec2(offset, 0.0);
+            let color = if hitsplat.damage == 0 {
+                SPLASH_COLOR
+            } else {
+                DAMAGE_COLOR
+            };
+
+            painter.circle(
+                center,
+                HITSPLAT_RADIUS,
+                color,
+                egui::Stroke::new(1.0, egui::Color32::BLACK),
+            );
+            painter.text(
+                center,
+                egui::Align2::CENTER_CENTER,
+                hitsplat.damage.to_string(),
+                egui::FontId::proportional(12.0),
+                egui::Color32::WHITE,
+            );
+        }
+    }
+}
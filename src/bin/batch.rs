@@ -0,0 +1,213 @@
+use maiden::mode::Mode;
+use maiden::party::party_preset;
+use maiden::scenario::MAX_PLAYERS;
+use maiden::sim::{Strategy, Wave, STRATEGIES};
+use maiden::spawn_point::{format_spawns, generate_spawn_points, scuff_spawns, wave_rng};
+use std::collections::HashMap;
+
+const USAGE: &str = "Usage: maiden-batch [OPTIONS]
+
+Simulates seeded waves against a scripted freezer and prints leak statistics.
+End tick is when the wave was over: every nylo left frozen, with any leaks already in.
+
+Options:
+    --waves <N>            Number of waves to simulate (default 1000)
+    --seed <SEED>          Seed for the first wave (default random)
+    --players <N>          Party size up to a full team, which sets the spawn count (default 1)
+    --strategy <NAME>      greedy, closest, north-first, south-first or random (default greedy)
+    --stacking <yes|no>    Whether nylos can share a tile (default yes)
+    --mode <NAME>          entry, normal or hard, which sets the numbers and timings (default normal)
+    --scuffed <0-1>        Chance of each spawn coming up late (default the mode's)
+    --top <N>              Number of spawn patterns to list (default 20)
+    --help                 Print this message";
+
+struct BatchOptions {
+    waves: u64,
+    seed: u64,
+    players: usize,
+    strategy: Strategy,
+    stacking: bool,
+    mode: Mode,
+    scuffed_chance: Option<f64>,
+    top: usize,
+}
+
+#[derive(Default)]
+struct PatternStats {
+    waves: u64,
+    leaky_waves: u64,
+    leaks: u64,
+    casts: u64,
+    spread_freezes: u64,
+    stacked_freezes: u64,
+    end_ticks: u64,
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+
+    let options = parse_args(args.into_iter()).unwrap_or_else(|err| {
+        eprintln!("{}\n\n{}", err, USAGE);
+        std::process::exit(2);
+    });
+
+    let mut patterns: HashMap<String, PatternStats> = HashMap::new();
+    let mut total = PatternStats::default();
+
+    // Each wave uses the same seed scheme as the game, so a seeded wave here
+    // is the same spawn a trainee sees with the same seed and wave number
+    for wave_number in 0..options.waves {
+        let mut rng = wave_rng(options.seed, wave_number);
+        let spawns = generate_spawn_points(&mut rng, party_preset(options.players).spawns);
+
+        let stats = options.mode.stats();
+        let scuffed_chance = options.scuffed_chance.unwrap_or(stats.scuffed_chance);
+        let delays = scuff_spawns(&mut rng, &spawns, scuffed_chance, stats.max_scuff_ticks);
+
+        let mut wave = Wave::new(&spawns);
+        wave.delay_spawns(&delays);
+        wave.stacking = options.stacking;
+        wave.set_hitpoints(
+            options
+                .mode
+                .hitpoints(party_preset(options.players).nylo_hitpoints),
+        );
+        options.strategy.play(&mut wave, &mut rng);
+
+        for stats in [
+            patterns.entry(format_spawns(&spawns)).or_default(),
+            &mut total,
+        ] {
+            stats.waves += 1;
+            stats.leaky_waves += (wave.leaks > 0) as u64;
+            stats.leaks += wave.leaks as u64;
+            stats.casts += wave.casts as u64;
+            stats.spread_freezes += wave.multi_freezes.spread as u64;
+            stats.stacked_freezes += wave.multi_freezes.stacked as u64;
+            stats.end_ticks += wave.tick as u64;
+        }
+    }
+
+    let strategy_name = STRATEGIES
+        .iter()
+        .find(|(_, strategy)| *strategy == options.strategy)
+        .map(|(name, _)| *name)
+        .unwrap();
+
+    println!(
+        "{} waves, seed {}, {} player(s), {} mode, {} strategy",
+        options.waves,
+        options.seed,
+        options.players,
+        options.mode.name(),
+        strategy_name
+    );
+    println!();
+    print_header();
+    print_row("All spawns", &total);
+    println!();
+
+    let mut patterns: Vec<(String, PatternStats)> = patterns.into_iter().collect();
+    patterns.sort_by(|(a_name, a), (b_name, b)| {
+        leak_rate(b)
+            .total_cmp(&leak_rate(a))
+            .then(b.waves.cmp(&a.waves))
+            .then(a_name.cmp(b_name))
+    });
+
+    println!("Leakiest spawn patterns:");
+    print_header();
+    for (name, stats) in patterns.iter().take(options.top) {
+        print_row(name, stats);
+    }
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<BatchOptions, String> {
+    let mut options = BatchOptions {
+        waves: 1000,
+        seed: rand::random::<u32>() as u64,
+        players: 1,
+        strategy: Strategy::Greedy,
+        stacking: true,
+        mode: Mode::Normal,
+        scuffed_chance: None,
+        top: 20,
+    };
+
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("{} requires a value", arg))?;
+        let invalid = || format!("Invalid value \"{}\" for {}", value, arg);
+
+        match arg.as_str() {
+            "--waves" => {
+                options.waves = value
+                    .parse()
+                    .ok()
+                    .filter(|waves: &u64| *waves > 0)
+                    .ok_or_else(invalid)?
+            }
+            "--seed" => options.seed = value.parse().map_err(|_| invalid())?,
+            "--players" => {
+                options.players = value
+                    .parse()
+                    .ok()
+                    .filter(|p: &usize| (1..=MAX_PLAYERS).contains(p))
+                    .ok_or_else(invalid)?
+            }
+            "--strategy" => options.strategy = Strategy::from_name(&value).ok_or_else(invalid)?,
+            "--stacking" => {
+                options.stacking = match value.as_str() {
+                    "yes" => true,
+                    "no" => false,
+                    _ => return Err(invalid()),
+                }
+            }
+            "--mode" => options.mode = Mode::from_name(&value).ok_or_else(invalid)?,
+            "--scuffed" => {
+                options.scuffed_chance = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|chance: &f64| (0.0..=1.0).contains(chance))
+                        .ok_or_else(invalid)?,
+                )
+            }
+            "--top" => options.top = value.parse().map_err(|_| invalid())?,
+            _ => return Err(format!("Unknown option \"{}\"", arg)),
+        }
+    }
+
+    Ok(options)
+}
+
+fn leak_rate(stats: &PatternStats) -> f64 {
+    stats.leaky_waves as f64 / stats.waves as f64
+}
+
+fn print_header() {
+    println!(
+        "{:<24} {:>7} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10}",
+        "Spawn", "Waves", "Leak rate", "Leaks", "Casts", "Multi", "Stacked", "End tick"
+    );
+}
+
+fn print_row(name: &str, stats: &PatternStats) {
+    let waves = stats.waves as f64;
+    println!(
+        "{:<24} {:>7} {:>9.1}% {:>10.2} {:>10.2} {:>10.2} {:>10.2} {:>10.2}",
+        name,
+        stats.waves,
+        100.0 * leak_rate(stats),
+        stats.leaks as f64 / waves,
+        stats.casts as f64 / waves,
+        stats.spread_freezes as f64 / waves,
+        stats.stacked_freezes as f64 / waves,
+        stats.end_ticks as f64 / waves,
+    );
+}
@@ -0,0 +1,197 @@
+use crate::billboard::Hitsplats;
+use crate::camera;
+use crate::config::Config;
+use crate::cursor::tile_center;
+use crate::inventory::Protection;
+use crate::matomenos::{CurrentSpawn, MoveNylos};
+use crate::overlay::{painter, Projector};
+use crate::player::Player;
+use crate::tick::on_game_tick;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use rand::Rng;
+
+pub struct BloodPlugin;
+
+// Label for the tick system that lands blood, for systems that react to it
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UpdateBlood;
+
+// Sent when a splat lands somewhere other than the player's tile
+pub struct BloodLanded(pub (f32, f32));
+
+// Ticks between the throw and the blood landing
+const BLOOD_FLIGHT_TICKS: u32 = 3;
+
+// Splats thrown near the player on top of the one aimed at their tile
+const EXTRA_SPLATS: usize = 2;
+const SPLAT_SPREAD: i32 = 3;
+
+const BLOOD_COLOR: egui::Color32 = egui::Color32::from_rgb(170, 10, 10);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SplatState {
+    // Ticks until it lands
+    Flying(u32),
+    // Ticks until it dries up
+    Pool(u32),
+}
+
+#[derive(Clone, Debug)]
+struct Splat {
+    tile: (f32, f32),
+    state: SplatState,
+}
+
+// Everything Maiden has thrown that is still in the air or on the floor
+#[derive(Clone, Default)]
+pub struct Blood {
+    splats: Vec<Splat>,
+}
+
+impl Blood {
+    pub fn add_pool(&mut self, tile: (f32, f32), ticks: u32) {
+        self.splats.push(Splat {
+            tile,
+            state: SplatState::Pool(ticks),
+        });
+    }
+
+    pub fn has_pool(&self, tile: (f32, f32)) -> bool {
+        self.splats
+            .iter()
+            .any(|splat| matches!(splat.state, SplatState::Pool(_)) && splat.tile == tile)
+    }
+}
+
+impl Plugin for BloodPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Blood>()
+            .add_event::<BloodLanded>()
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(on_game_tick)
+                    .with_system(update_blood.label(UpdateBlood).after(MoveNylos)),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Spawned).with_system(draw_blood))
+            .add_system_set(SystemSet::on_update(GameState::Paused).with_system(draw_blood))
+            .add_system_set(SystemSet::on_exit(GameState::Spawned).with_system(clear_blood));
+    }
+}
+
+// Maiden throws blood while the nylos are up, how often and how hard depending on the difficulty
+fn update_blood(
+    mut blood: ResMut<Blood>,
+    config: Res<Config>,
+    current_spawn: Res<CurrentSpawn>,
+    mut landed: EventWriter<BloodLanded>,
+    mut player_query: Query<(&Transform, &mut Player, &mut Hitsplats)>,
+) {
+    if !current_spawn.is_spawned() {
+        return;
+    }
+    let (transform, mut player, mut hitsplats) = match player_query.get_single_mut() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+    let player_tile = tile_center(transform.translation);
+    let stats = config.mode.stats();
+    let mut rng = rand::thread_rng();
+
+    for splat in blood.splats.iter_mut() {
+        splat.state = match splat.state {
+            SplatState::Flying(1) => {
+                // Blood that lands on the player's tile is Maiden's magic attack
+                if splat.tile == player_tile {
+                    let damage = rng.gen_range(0..=stats.magic_max_hit);
+                    hitsplats.add(player.hit(damage, Some(Protection::Magic)));
+                } else {
+                    landed.send(BloodLanded(splat.tile));
+                }
+                SplatState::Pool(stats.blood_pool_ticks)
+            }
+            SplatState::Flying(ticks) => SplatState::Flying(ticks - 1),
+            SplatState::Pool(ticks) => SplatState::Pool(ticks.saturating_sub(1)),
+        };
+    }
+    blood
+        .splats
+        .retain(|splat| splat.state != SplatState::Pool(0));
+
+    // Standing in any number of pools only hurts once a tick
+    if blood.has_pool(player_tile) {
+        // Pools can't be prayed against
+        let damage = rng.gen_range(1..=stats.pool_max_hit);
+        hitsplats.add(player.hit(damage, None));
+    }
+
+    let tick = current_spawn.tick();
+    if tick > 0 && tick.is_multiple_of(stats.attack_ticks) {
+        blood.splats.push(Splat {
+            tile: player_tile,
+            state: SplatState::Flying(BLOOD_FLIGHT_TICKS),
+        });
+        for _ in 0..EXTRA_SPLATS {
+            let dx = rng.gen_range(-SPLAT_SPREAD..=SPLAT_SPREAD) as f32;
+            let dz = rng.gen_range(-SPLAT_SPREAD..=SPLAT_SPREAD) as f32;
+            blood.splats.push(Splat {
+                tile: (player_tile.0 + dx, player_tile.1 + dz),
+                state: SplatState::Flying(BLOOD_FLIGHT_TICKS),
+            });
+        }
+    }
+}
+
+// Incoming blood as an outline with the ticks until it lands, pools as filled tiles
+fn draw_blood(
+    mut egui_context: ResMut<EguiContext>,
+    blood: Res<Blood>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<camera::Camera>>,
+) {
+    let projector = match camera_query
+        .get_single()
+        .ok()
+        .and_then(|(camera, transform)| Projector::new(camera, transform))
+    {
+        Some(projector) => projector,
+        None => return,
+    };
+    let painter = painter(egui_context.ctx_mut(), "blood");
+
+    for splat in &blood.splats {
+        let (x, z) = splat.tile;
+        let square = match projector.floor_square(x, z, 0.5) {
+            Some(square) => square,
+            None => continue,
+        };
+        match splat.state {
+            SplatState::Flying(ticks) => {
+                painter.add(egui::Shape::closed_line(
+                    square,
+                    egui::Stroke::new(2.0, BLOOD_COLOR),
+                ));
+                if let Some(center) = projector.project(Vec3::new(x, 0.0, z)) {
+                    painter.text(
+                        center,
+                        egui::Align2::CENTER_CENTER,
+                        ticks.to_string(),
+                        egui::FontId::proportional(14.0),
+                        egui::Color32::WHITE,
+                    );
+                }
+            }
+            SplatState::Pool(_) => {
+                painter.add(egui::Shape::convex_polygon(
+                    square,
+                    BLOOD_COLOR.linear_multiply(0.6),
+                    egui::Stroke::new(1.0, BLOOD_COLOR),
+                ));
+            }
+        }
+    }
+}
+
+fn clear_blood(mut blood: ResMut<Blood>) {
+    blood.splats.clear();
+}
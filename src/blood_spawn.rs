@@ -0,0 +1,265 @@
+use crate::actions::Actions;
+use crate::billboard::Hitsplats;
+use crate::blood::{Blood, BloodLanded, UpdateBlood};
+use crate::clickbox::ConvexHull;
+use crate::config::Config;
+use crate::cursor::tile_center;
+use crate::overlay::folded_window;
+use crate::player::Player;
+use crate::sim::reaches_maiden;
+use crate::tick::on_game_tick;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_egui::EguiContext;
+use rand::Rng;
+
+pub struct BloodSpawnPlugin;
+
+// Hitpoints of a blood spawn in normal mode
+const BLOOD_SPAWN_HITPOINTS: u32 = 120;
+
+// Blood spawns float this far above the floor, and are clicked with a cube this size
+const BLOOD_SPAWN_HEIGHT: f32 = 0.4;
+pub const BLOOD_SPAWN_SIZE: f32 = 0.8;
+
+const MAX_BLOOD_SPAWNS: usize = 4;
+
+// Chance of a blood spawn rising from a splat that lands away from the player
+const SPAWN_CHANCE: f64 = 0.3;
+
+// Ticks the trail left behind a blood spawn stays on the floor
+const TRAIL_TICKS: u32 = 6;
+
+// A plain melee attack, since blood spawns can't be frozen
+const ATTACK_DELAY: i32 = 4;
+
+// Melee only reaches the tiles around the player
+const ATTACK_RANGE: f32 = 1.0;
+
+// Floor the blood spawns wander around on, away from Maiden
+const ROOM_X: (f32, f32) = (9.0, 27.0);
+const ROOM_Z: (f32, f32) = (1.0, 23.0);
+
+pub struct BloodSpawnSettings {
+    pub enabled: bool,
+    pub spawn_healing: bool,
+}
+
+impl Default for BloodSpawnSettings {
+    fn default() -> BloodSpawnSettings {
+        BloodSpawnSettings {
+            enabled: true,
+            spawn_healing: false,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct BloodSpawn {
+    hitpoints: u32,
+    max_hitpoints: u32,
+}
+
+impl BloodSpawn {
+    pub fn hitpoints(&self) -> u32 {
+        self.hitpoints
+    }
+
+    pub fn max_hitpoints(&self) -> u32 {
+        self.max_hitpoints
+    }
+
+    pub fn state(&self, transform: &Transform) -> BloodSpawnState {
+        BloodSpawnState {
+            translation: transform.translation,
+            hitpoints: self.hitpoints,
+            max_hitpoints: self.max_hitpoints,
+        }
+    }
+}
+
+// Everything needed to put a blood spawn back exactly as it was
+#[derive(Clone)]
+pub struct BloodSpawnState {
+    translation: Vec3,
+    hitpoints: u32,
+    max_hitpoints: u32,
+}
+
+pub struct BloodSpawnAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for BloodSpawnAssets {
+    fn from_world(world: &mut World) -> BloodSpawnAssets {
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Mesh::from(shape::UVSphere {
+                radius: BLOOD_SPAWN_SIZE / 2.0,
+                ..Default::default()
+            }));
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(Color::rgb(0.6, 0.02, 0.02).into());
+        BloodSpawnAssets { mesh, material }
+    }
+}
+
+// The shape blood spawns are picked with, relative to their translation
+pub fn blood_spawn_hull() -> ConvexHull {
+    ConvexHull::cube(BLOOD_SPAWN_SIZE)
+}
+
+pub struct BloodSpawnAttacked(pub Entity);
+
+impl Plugin for BloodSpawnPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BloodSpawnSettings>()
+            .init_resource::<BloodSpawnAssets>()
+            .add_event::<BloodSpawnAttacked>()
+            .add_system(blood_spawn_ui)
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(on_game_tick)
+                    .with_system(move_blood_spawns.after(UpdateBlood)),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Spawned).with_system(handle_attacks))
+            .add_system_set(SystemSet::on_update(GameState::Paused).with_system(handle_attacks))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Spawned).with_system(despawn_blood_spawns),
+            );
+    }
+}
+
+pub fn spawn_blood_spawn(
+    commands: &mut Commands,
+    assets: &Res<BloodSpawnAssets>,
+    blood_spawn: &BloodSpawnState,
+) {
+    commands
+        .spawn_bundle(PbrBundle {
+            mesh: assets.mesh.clone(),
+            material: assets.material.clone(),
+            transform: Transform::from_translation(blood_spawn.translation),
+            ..Default::default()
+        })
+        .insert(Name::new("Blood spawn"))
+        .insert(BloodSpawn {
+            hitpoints: blood_spawn.hitpoints,
+            max_hitpoints: blood_spawn.max_hitpoints,
+        })
+        .insert(Hitsplats::new(0.0));
+}
+
+fn blood_spawn_ui(mut egui_context: ResMut<EguiContext>, mut settings: ResMut<BloodSpawnSettings>) {
+    folded_window(egui_context.ctx_mut(), "Blood spawns").show(egui_context.ctx_mut(), |ui| {
+        ui.checkbox(&mut settings.enabled, "Spawn from Maiden's blood");
+        ui.checkbox(&mut settings.spawn_healing, "Blood spawns heal standing in blood");
+    });
+}
+
+fn on_floor((x, z): (f32, f32)) -> bool {
+    (ROOM_X.0..ROOM_X.1).contains(&x) && (ROOM_Z.0..ROOM_Z.1).contains(&z) && !reaches_maiden(x, z)
+}
+
+// Blood spawns wander a tile at a time, leaving blood behind them, and heal
+// themselves standing in blood when spawn_healing is on
+fn move_blood_spawns(
+    mut commands: Commands,
+    settings: Res<BloodSpawnSettings>,
+    config: Res<Config>,
+    assets: Res<BloodSpawnAssets>,
+    mut blood: ResMut<Blood>,
+    mut landed: EventReader<BloodLanded>,
+    mut query: Query<(&mut BloodSpawn, &mut Transform)>,
+) {
+    let mut rng = rand::thread_rng();
+    let mut count = 0;
+
+    for (mut blood_spawn, mut transform) in query.iter_mut() {
+        count += 1;
+        let (x, z) = tile_center(transform.translation);
+        let (dx, dz) = (rng.gen_range(-1..=1) as f32, rng.gen_range(-1..=1) as f32);
+        if (dx, dz) != (0.0, 0.0) && on_floor((x + dx, z + dz)) {
+            blood.add_pool((x, z), TRAIL_TICKS);
+            transform.translation.x = x + dx;
+            transform.translation.z = z + dz;
+        }
+
+        if settings.spawn_healing && blood.has_pool(tile_center(transform.translation)) {
+            blood_spawn.hitpoints = std::cmp::min(
+                blood_spawn.hitpoints + config.mode.stats().blood_spawn_heal,
+                blood_spawn.max_hitpoints,
+            );
+        }
+    }
+
+    let hitpoints = config.mode.hitpoints(BLOOD_SPAWN_HITPOINTS);
+    for BloodLanded(tile) in landed.iter() {
+        if !settings.enabled || count >= MAX_BLOOD_SPAWNS || !on_floor(*tile) {
+            continue;
+        }
+        if rng.gen_bool(SPAWN_CHANCE) {
+            spawn_blood_spawn(
+                &mut commands,
+                &assets,
+                &BloodSpawnState {
+                    translation: Vec3::new(tile.0, BLOOD_SPAWN_HEIGHT, tile.1),
+                    hitpoints,
+                    max_hitpoints: hitpoints,
+                },
+            );
+            count += 1;
+        }
+    }
+}
+
+// A blood spawn out of reach isn't hit, the player walks up next to it instead
+fn handle_attacks(
+    mut commands: Commands,
+    mut events: EventReader<BloodSpawnAttacked>,
+    mut blood_spawns_query: Query<(&Transform, &mut BloodSpawn, &mut Hitsplats)>,
+    mut player_query: Query<(&Transform, &mut Player)>,
+    mut actions: ResMut<Actions>,
+    config: Res<Config>,
+) {
+    let (player_transform, mut player) = player_query.single_mut();
+    let mut rng = rand::thread_rng();
+
+    for BloodSpawnAttacked(entity) in events.iter() {
+        if player.attack_delay != 0 {
+            continue;
+        }
+        let (transform, mut blood_spawn, mut hitsplats) = match blood_spawns_query.get_mut(*entity)
+        {
+            Ok(blood_spawn) => blood_spawn,
+            Err(_) => continue,
+        };
+
+        let (x, z) = tile_center(player_transform.translation);
+        let (target_x, target_z) = tile_center(transform.translation);
+        if (x - target_x).abs() > ATTACK_RANGE || (z - target_z).abs() > ATTACK_RANGE {
+            actions.walk_target = Some(Vec2::new(
+                target_x + (x - target_x).clamp(-ATTACK_RANGE, ATTACK_RANGE),
+                target_z + (z - target_z).clamp(-ATTACK_RANGE, ATTACK_RANGE),
+            ));
+            continue;
+        }
+
+        let damage = rng.gen_range(0..=config.mode.stats().blood_spawn_max_hit);
+        blood_spawn.hitpoints = blood_spawn.hitpoints.saturating_sub(damage);
+        hitsplats.add(damage);
+        player.attack_delay = ATTACK_DELAY;
+
+        if blood_spawn.hitpoints == 0 {
+            commands.entity(*entity).despawn_recursive();
+        }
+    }
+}
+
+fn despawn_blood_spawns(mut commands: Commands, query: Query<Entity, With<BloodSpawn>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
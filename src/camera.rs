@@ -4,7 +4,6 @@ use crate::GameState;
 
 use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
 use bevy::prelude::*;
-use bevy_mod_picking::prelude::*;
 use smooth_bevy_cameras::{
     controllers::orbit::{
         ControlEvent, OrbitCameraBundle, OrbitCameraController, OrbitCameraPlugin,
@@ -17,6 +16,10 @@ pub struct CameraPlugin;
 #[derive(Component)]
 pub struct Camera;
 
+// Where the camera starts in the room, looking across the spawns from Maiden's side
+pub const CAMERA_EYE: Vec3 = Vec3::new(-2.0, 10.0, 5.0);
+pub const CAMERA_TARGET: Vec3 = Vec3::new(10.0, 1.0, 10.0);
+
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(LookTransformPlugin)
@@ -31,6 +34,11 @@ impl Plugin for CameraPlugin {
                 SystemSet::on_update(GameState::Spawned)
                     .with_system(follow_player)
                     .with_system(control_camera),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Paused)
+                    .with_system(follow_player)
+                    .with_system(control_camera),
             );
     }
 }
@@ -40,10 +48,9 @@ fn spawn_camera(mut commands: Commands) {
         .spawn_bundle(Camera3dBundle::default())
         .insert_bundle(OrbitCameraBundle::new(
             OrbitCameraController::default(),
-            Vec3::new(-2.0, 10.0, 5.0),
-            Vec3::new(10.0, 1.0, 10.0),
+            CAMERA_EYE,
+            CAMERA_TARGET,
         ))
-        .insert(PickRaycastSource::default())
         .insert(Name::new("Camera"))
         .insert(Camera);
 }
@@ -53,7 +60,7 @@ fn follow_player(
     player: Query<&Transform, With<Player>>,
     mut camera: Query<&mut LookTransform, With<Camera>>,
 ) {
-    if actions.player_movement.is_none() {
+    if !actions.is_moving() {
         return;
     }
 
@@ -0,0 +1,220 @@
+use crate::spawn_point::{format_spawns, SpawnPoint};
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+pub struct ChatPlugin;
+
+// Oldest messages are dropped past this many
+const MAX_MESSAGES: usize = 200;
+
+const CHAT_OFFSET: egui::Vec2 = egui::vec2(8.0, -8.0);
+const CHAT_SIZE: egui::Vec2 = egui::vec2(380.0, 130.0);
+
+const TIMESTAMP_COLOR: egui::Color32 = egui::Color32::GRAY;
+const GAME_COLOR: egui::Color32 = egui::Color32::from_rgb(240, 60, 60);
+const SENDER_COLOR: egui::Color32 = egui::Color32::from_rgb(80, 160, 255);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Channel {
+    // Messages from the game itself
+    Game,
+    // What teammates say
+    Public,
+}
+
+impl Channel {
+    fn label(&self) -> &'static str {
+        match self {
+            Channel::Game => "Game",
+            Channel::Public => "Public",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct ChatMessage {
+    // Wave of the session and tick of that wave the message was sent on
+    wave: u32,
+    tick: u32,
+    channel: Channel,
+    sender: Option<String>,
+    text: String,
+}
+
+impl ChatMessage {
+    fn timestamp(&self) -> String {
+        format!("[W{} T{}]", self.wave, self.tick)
+    }
+
+    fn body(&self) -> String {
+        match &self.sender {
+            Some(sender) => format!("{}: {}", sender, self.text),
+            None => self.text.clone(),
+        }
+    }
+}
+
+// Game messages and teammate call-outs, like the chatbox in game
+#[derive(Default)]
+pub struct Chat {
+    messages: Vec<ChatMessage>,
+    // Waves started this session, reruns included, so each attempt is told apart
+    wave: u32,
+}
+
+impl Chat {
+    pub fn start_wave(&mut self) {
+        self.wave += 1;
+    }
+
+    // Drops what the current wave said after the tick, for when it's rewound there
+    pub fn rewind(&mut self, tick: u32) {
+        let wave = self.wave;
+        self.messages
+            .retain(|message| message.wave != wave || message.tick <= tick);
+    }
+
+    pub fn game(&mut self, tick: u32, text: &str) {
+        self.push(ChatMessage {
+            wave: self.wave,
+            tick,
+            channel: Channel::Game,
+            sender: None,
+            text: text.to_string(),
+        });
+    }
+
+    pub fn public(&mut self, tick: u32, sender: &str, text: &str) {
+        self.push(ChatMessage {
+            wave: self.wave,
+            tick,
+            channel: Channel::Public,
+            sender: Some(sender.to_string()),
+            text: text.to_string(),
+        });
+    }
+
+    fn push(&mut self, message: ChatMessage) {
+        self.messages.push(message);
+        if self.messages.len() > MAX_MESSAGES {
+            self.messages.remove(0);
+        }
+    }
+}
+
+// What the chatbox shows: one channel or all of them, and only lines containing the search
+#[derive(Default)]
+struct ChatFilter {
+    channel: Option<Channel>,
+    search: String,
+}
+
+impl ChatFilter {
+    fn matches(&self, message: &ChatMessage) -> bool {
+        self.channel
+            .is_none_or(|channel| message.channel == channel)
+            && (self.search.is_empty()
+                || message
+                    .body()
+                    .to_lowercase()
+                    .contains(&self.search.to_lowercase()))
+    }
+}
+
+// Each teammate calls the two spawns they'll freeze, counting north to south
+// and leaving the northmost pair to the player
+pub fn teammate_callouts(spawns: &[SpawnPoint], teammates: usize) -> Vec<(String, String)> {
+    let mut spawns = spawns.to_vec();
+    spawns.sort_by_key(SpawnPoint::index);
+
+    spawns
+        .chunks(2)
+        .skip(1)
+        .take(teammates)
+        .enumerate()
+        .map(|(i, claimed)| {
+            (
+                format!("Teammate {}", i + 1),
+                format!("{} mine", format_spawns(claimed)),
+            )
+        })
+        .collect()
+}
+
+impl Plugin for ChatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Chat>()
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(draw_chat))
+            .add_system_set(SystemSet::on_update(GameState::Spawned).with_system(draw_chat))
+            .add_system_set(SystemSet::on_update(GameState::Paused).with_system(draw_chat));
+    }
+}
+
+fn draw_chat(
+    mut egui_context: ResMut<EguiContext>,
+    chat: Res<Chat>,
+    mut filter: Local<ChatFilter>,
+) {
+    egui::Area::new("chat")
+        .anchor(egui::Align2::LEFT_BOTTOM, CHAT_OFFSET)
+        .show(egui_context.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.set_width(CHAT_SIZE.x);
+
+                let visible: Vec<&ChatMessage> = chat
+                    .messages
+                    .iter()
+                    .filter(|message| filter.matches(message))
+                    .collect();
+
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut filter.channel, None, "All");
+                    for channel in [Channel::Game, Channel::Public] {
+                        ui.selectable_value(&mut filter.channel, Some(channel), channel.label());
+                    }
+                    ui.add(
+                        egui::TextEdit::singleline(&mut filter.search)
+                            .hint_text("Filter")
+                            .desired_width(100.0),
+                    );
+                    if ui.button("Copy").clicked() {
+                        ui.output().copied_text = visible
+                            .iter()
+                            .map(|message| format!("{} {}", message.timestamp(), message.body()))
+                            .collect::<Vec<String>>()
+                            .join("\n");
+                    }
+                });
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .max_height(CHAT_SIZE.y)
+                    .auto_shrink([false, false])
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for message in visible {
+                            ui.horizontal_wrapped(|ui| {
+                                ui.label(
+                                    egui::RichText::new(message.timestamp())
+                                        .color(TIMESTAMP_COLOR)
+                                        .monospace(),
+                                );
+                                if let Some(sender) = &message.sender {
+                                    ui.label(
+                                        egui::RichText::new(format!("{}:", sender))
+                                            .color(SENDER_COLOR)
+                                            .strong(),
+                                    );
+                                }
+                                let color = match message.channel {
+                                    Channel::Game => GAME_COLOR,
+                                    Channel::Public => egui::Color32::WHITE,
+                                };
+                                ui.label(egui::RichText::new(&message.text).color(color));
+                            });
+                        }
+                    });
+            });
+        });
+}
@@ -0,0 +1,402 @@
+use crate::camera;
+use crate::cursor::{Hovered, Ray};
+use crate::loading::ModelAssets;
+use crate::matomenos::{model_transform, Matomenos};
+use crate::overlay::{folded_window, painter, Projector};
+use crate::GameState;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+use bevy::render::primitives::Aabb;
+use bevy_egui::{egui, EguiContext};
+
+pub struct ClickboxPlugin;
+
+// Size of the cube nylos were originally clicked with
+const CUBE_SIZE: f32 = 1.75;
+
+// Difficulty presets for the clickbox size
+const SIZE_PRESETS: &[(&str, f32)] = &[("Generous", 1.25), ("Normal", 1.0), ("Tight", 0.8)];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ClickboxShape {
+    // Convex hull around the Matomenos model
+    Model,
+    Cube,
+}
+
+pub struct ClickboxSettings {
+    pub shape: ClickboxShape,
+    pub scale: f32,
+    pub visualise: bool,
+}
+
+impl Default for ClickboxSettings {
+    fn default() -> ClickboxSettings {
+        ClickboxSettings {
+            shape: ClickboxShape::Model,
+            scale: 1.0,
+            visualise: false,
+        }
+    }
+}
+
+// The shape every nylo is picked with, relative to the nylo's translation
+pub struct Clickbox {
+    pub hull: ConvexHull,
+    pub mesh: Handle<Mesh>,
+}
+
+impl FromWorld for Clickbox {
+    fn from_world(world: &mut World) -> Clickbox {
+        let hull = ConvexHull::cube(CUBE_SIZE);
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(hull.mesh());
+        Clickbox { hull, mesh }
+    }
+}
+
+// A convex outline on the floor extruded between two heights
+#[derive(Clone, Debug)]
+pub struct ConvexHull {
+    // Counter-clockwise in (x, z)
+    outline: Vec<Vec2>,
+    bottom: f32,
+    top: f32,
+}
+
+impl ConvexHull {
+    pub fn cube(size: f32) -> ConvexHull {
+        let half = size / 2.0;
+        ConvexHull {
+            outline: vec![
+                Vec2::new(-half, -half),
+                Vec2::new(half, -half),
+                Vec2::new(half, half),
+                Vec2::new(-half, half),
+            ],
+            bottom: -half,
+            top: half,
+        }
+    }
+
+    pub fn from_points(points: &[Vec3]) -> Option<ConvexHull> {
+        let bottom = points.iter().map(|point| point.y).reduce(f32::min)?;
+        let top = points.iter().map(|point| point.y).reduce(f32::max)?;
+        let outline = convex_outline(
+            points
+                .iter()
+                .map(|point| Vec2::new(point.x, point.z))
+                .collect(),
+        );
+        (outline.len() >= 3).then_some(ConvexHull {
+            outline,
+            bottom,
+            top,
+        })
+    }
+
+    // Grows or shrinks the hull about its centre
+    pub fn scaled(&self, scale: f32) -> ConvexHull {
+        let center = self
+            .outline
+            .iter()
+            .fold(Vec2::ZERO, |sum, point| sum + *point)
+            / self.outline.len() as f32;
+        let middle = (self.bottom + self.top) / 2.0;
+        let half_height = (self.top - self.bottom) / 2.0 * scale;
+        ConvexHull {
+            outline: self
+                .outline
+                .iter()
+                .map(|point| center + (*point - center) * scale)
+                .collect(),
+            bottom: middle - half_height,
+            top: middle + half_height,
+        }
+    }
+
+    // Every face as an outward normal and the distance of the face from the origin along it
+    fn planes(&self) -> impl Iterator<Item = (Vec3, f32)> + '_ {
+        let sides = self.sides().map(|(a, b)| {
+            let edge = b - a;
+            let normal = Vec2::new(edge.y, -edge.x).normalize();
+            (Vec3::new(normal.x, 0.0, normal.y), normal.dot(a))
+        });
+        [(Vec3::Y, self.top), (Vec3::NEG_Y, -self.bottom)]
+            .into_iter()
+            .chain(sides)
+    }
+
+    fn sides(&self) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        let next = self.outline.iter().cycle().skip(1);
+        self.outline.iter().copied().zip(next.copied())
+    }
+
+    // Distance along the ray to the hull placed at the given position, if it hits it at all
+    pub fn intersect(&self, ray: &Ray, position: Vec3) -> Option<f32> {
+        let origin = ray.origin - position;
+        let mut t_near = 0.0f32;
+        let mut t_far = f32::INFINITY;
+
+        for (normal, distance) in self.planes() {
+            let facing = normal.dot(ray.direction);
+            let gap = distance - normal.dot(origin);
+            if facing.abs() < f32::EPSILON {
+                // Parallel to this face, so it's either always inside or always outside it
+                if gap < 0.0 {
+                    return None;
+                }
+                continue;
+            }
+
+            let t = gap / facing;
+            if facing < 0.0 {
+                t_near = t_near.max(t);
+            } else {
+                t_far = t_far.min(t);
+            }
+            if t_near > t_far {
+                return None;
+            }
+        }
+
+        Some(t_near)
+    }
+
+    pub fn top(&self) -> f32 {
+        self.top
+    }
+
+    // Every corner of the hull, top and bottom
+    pub fn corners(&self) -> Vec<Vec3> {
+        self.outline
+            .iter()
+            .flat_map(|point| {
+                [
+                    Vec3::new(point.x, self.bottom, point.y),
+                    Vec3::new(point.x, self.top, point.y),
+                ]
+            })
+            .collect()
+    }
+
+    // Line segments outlining the hull, for the visualiser
+    pub fn edges(&self) -> Vec<(Vec3, Vec3)> {
+        let bottom = |point: Vec2| Vec3::new(point.x, self.bottom, point.y);
+        let top = |point: Vec2| Vec3::new(point.x, self.top, point.y);
+        self.sides()
+            .flat_map(|(a, b)| {
+                [
+                    (bottom(a), bottom(b)),
+                    (top(a), top(b)),
+                    (bottom(a), top(a)),
+                ]
+            })
+            .collect()
+    }
+
+    // A flat shaded mesh of the hull, drawn over the nylo in the freeze colour
+    pub fn mesh(&self) -> Mesh {
+        let mut triangles = Vec::new();
+        let bottom = |point: Vec2| Vec3::new(point.x, self.bottom, point.y);
+        let top = |point: Vec2| Vec3::new(point.x, self.top, point.y);
+
+        for (a, b) in self.sides() {
+            let edge = b - a;
+            let outward = Vec3::new(edge.y, 0.0, -edge.x).normalize();
+            triangles.push(([bottom(a), bottom(b), top(b)], outward));
+            triangles.push(([bottom(a), top(b), top(a)], outward));
+        }
+        for window in self.outline[1..].windows(2) {
+            let first = self.outline[0];
+            triangles.push(([top(first), top(window[0]), top(window[1])], Vec3::Y));
+            triangles.push((
+                [bottom(first), bottom(window[0]), bottom(window[1])],
+                Vec3::NEG_Y,
+            ));
+        }
+
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        for ([a, mut b, mut c], outward) in triangles {
+            // Bevy culls faces that aren't wound counter-clockwise from the outside
+            if (b - a).cross(c - a).dot(outward) < 0.0 {
+                std::mem::swap(&mut b, &mut c);
+            }
+            positions.extend([a, b, c].map(|point| point.to_array()));
+            normals.extend([outward.to_array(); 3]);
+        }
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.set_indices(Some(Indices::U32((0..positions.len() as u32).collect())));
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0f32, 0.0]; positions.len()]);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh
+    }
+}
+
+// Andrew's monotone chain, returning the hull counter-clockwise
+pub fn convex_outline(mut points: Vec<Vec2>) -> Vec<Vec2> {
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    let turns_left = |chain: &[Vec2], point: Vec2| match chain {
+        [.., a, b] => (*b - *a).perp_dot(point - *a) > 0.0,
+        _ => true,
+    };
+    let mut lower: Vec<Vec2> = Vec::new();
+    for point in points.iter().copied() {
+        while !turns_left(&lower, point) {
+            lower.pop();
+        }
+        lower.push(point);
+    }
+    let mut upper: Vec<Vec2> = Vec::new();
+    for point in points.iter().rev().copied() {
+        while !turns_left(&upper, point) {
+            upper.pop();
+        }
+        upper.push(point);
+    }
+
+    lower.pop();
+    upper.pop();
+    lower.extend(upper);
+    lower
+}
+
+fn model_hull(mesh: &Mesh) -> Option<ConvexHull> {
+    let positions = match mesh.attribute(Mesh::ATTRIBUTE_POSITION)? {
+        VertexAttributeValues::Float32x3(positions) => positions,
+        _ => return None,
+    };
+    let indices: Vec<usize> = match mesh.indices()? {
+        Indices::U16(indices) => indices.iter().map(|index| *index as usize).collect(),
+        Indices::U32(indices) => indices.iter().map(|index| *index as usize).collect(),
+    };
+    // The glTF node stands the mesh upright before the model's own transform is applied
+    let transform =
+        model_transform().compute_matrix() * Mat4::from_rotation_x(std::f32::consts::FRAC_PI_2);
+
+    // The model has a cluster of stray vertices far above it, all in zero-area triangles
+    let mut points = Vec::new();
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| Vec3::from(positions[triangle[i]]));
+        if (b - a).cross(c - a).length_squared() > f32::EPSILON {
+            points.extend([a, b, c].map(|point| transform.transform_point3(point)));
+        }
+    }
+    ConvexHull::from_points(&points)
+}
+
+impl Plugin for ClickboxPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ClickboxSettings>()
+            .init_resource::<Clickbox>()
+            .add_system(clickbox_ui)
+            .add_system(update_clickbox)
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(draw_clickboxes))
+            .add_system_set(SystemSet::on_update(GameState::Spawned).with_system(draw_clickboxes))
+            .add_system_set(SystemSet::on_update(GameState::Paused).with_system(draw_clickboxes));
+    }
+}
+
+fn clickbox_ui(mut egui_context: ResMut<EguiContext>, mut settings: ResMut<ClickboxSettings>) {
+    folded_window(egui_context.ctx_mut(), "Clickboxes").show(egui_context.ctx_mut(), |ui| {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut settings.shape, ClickboxShape::Model, "Model");
+            ui.radio_value(&mut settings.shape, ClickboxShape::Cube, "Cube");
+        });
+        ui.horizontal(|ui| {
+            for (name, scale) in SIZE_PRESETS {
+                if ui
+                    .selectable_label(settings.scale == *scale, *name)
+                    .clicked()
+                {
+                    settings.scale = *scale;
+                }
+            }
+        });
+        ui.add(egui::Slider::new(&mut settings.scale, 0.5..=1.5).text("Size"));
+        ui.checkbox(&mut settings.visualise, "Show clickboxes");
+    });
+}
+
+fn update_clickbox(
+    mut commands: Commands,
+    settings: Res<ClickboxSettings>,
+    models: Option<Res<ModelAssets>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut clickbox: ResMut<Clickbox>,
+    nylos_query: Query<Entity, With<Matomenos>>,
+    mut loaded_hull: Local<Option<ConvexHull>>,
+) {
+    // The model only becomes available once loading has finished
+    let mut just_loaded = false;
+    if loaded_hull.is_none() {
+        *loaded_hull =
+            models.and_then(|models| meshes.get(&models.matomenos_mesh).and_then(model_hull));
+        just_loaded = loaded_hull.is_some();
+    }
+
+    if !settings.is_changed() && !just_loaded {
+        return;
+    }
+
+    let hull = match (settings.shape, loaded_hull.as_ref()) {
+        (ClickboxShape::Model, Some(hull)) => hull.clone(),
+        _ => ConvexHull::cube(CUBE_SIZE),
+    }
+    .scaled(settings.scale);
+
+    if let Some(mesh) = meshes.get_mut(&clickbox.mesh) {
+        *mesh = hull.mesh();
+    }
+    clickbox.hull = hull;
+
+    // Bounding boxes are only calculated for entities without one, so drop the stale ones
+    for entity in nylos_query.iter() {
+        commands.entity(entity).remove::<Aabb>();
+    }
+}
+
+fn draw_clickboxes(
+    mut egui_context: ResMut<EguiContext>,
+    settings: Res<ClickboxSettings>,
+    clickbox: Res<Clickbox>,
+    hovered: Res<Hovered>,
+    nylos_query: Query<(Entity, &Transform), With<Matomenos>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<camera::Camera>>,
+) {
+    if !settings.visualise {
+        return;
+    }
+    let projector = match camera_query
+        .get_single()
+        .ok()
+        .and_then(|(camera, transform)| Projector::new(camera, transform))
+    {
+        Some(projector) => projector,
+        None => return,
+    };
+    let painter = painter(egui_context.ctx_mut(), "clickboxes");
+    let edges = clickbox.hull.edges();
+
+    for (entity, transform) in nylos_query.iter() {
+        let color = if hovered.nylo == Some(entity) {
+            egui::Color32::WHITE
+        } else {
+            egui::Color32::from_rgba_unmultiplied(255, 0, 255, 160)
+        };
+        for (from, to) in &edges {
+            let from = projector.project(transform.translation + *from);
+            let to = projector.project(transform.translation + *to);
+            if let (Some(from), Some(to)) = (from, to) {
+                painter.line_segment([from, to], egui::Stroke::new(1.0, color));
+            }
+        }
+    }
+}
@@ -1,17 +1,74 @@
+use crate::launch::LaunchOptions;
 use crate::matomenos::CurrentSpawn;
+use crate::mode::{Mode, MODES};
+use crate::party::{party_preset, PARTY_PRESETS};
+use crate::replay::Replay;
+use crate::scenario::Scenario;
+use crate::tick::{toggle_pause, GameTick, MAX_SPEED, MIN_SPEED};
 use crate::GameState;
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContext};
 
 pub struct ConfigPlugin;
 
+const MAX_COUNTDOWN: u32 = 30;
+
+// How long the room waits before the nylos come up
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Countdown {
+    Fixed(u32),
+    // A new number of ticks each wave, between the two inclusive
+    Random(u32, u32),
+    // Until Maiden's simulated health drops to the spawn threshold
+    MaidenHealth,
+}
+
 pub struct Config {
     pub players: usize,
+    pub mode: Mode,
+    pub seed: Option<u64>,
+    pub hints: bool,
+    // Whether nylos can share a tile, like they do in game
+    pub stacking: bool,
+    // Chance of each spawn in a random wave coming up a tick or two late
+    pub scuffed_chance: f64,
+    pub countdown: Countdown,
+    // Cues for the wave coming up, like players watch for in game
+    pub chat_cue: bool,
+    pub health_bar_cue: bool,
 }
 
 impl Default for Config {
     fn default() -> Config {
-        Config { players: 1 }
+        Config {
+            players: 1,
+            mode: Mode::Normal,
+            seed: None,
+            hints: false,
+            stacking: true,
+            scuffed_chance: 0.0,
+            countdown: Countdown::Fixed(Mode::Normal.stats().countdown),
+            chat_cue: true,
+            health_bar_cue: true,
+        }
+    }
+}
+
+impl Config {
+    // Switches difficulty, resetting the spawn timing to the new mode's defaults
+    pub fn set_mode(&mut self, mode: Mode) {
+        if mode == self.mode {
+            return;
+        }
+        self.mode = mode;
+
+        let stats = mode.stats();
+        self.countdown = match self.countdown {
+            Countdown::Fixed(_) => Countdown::Fixed(stats.countdown),
+            Countdown::Random(..) => Countdown::Random(stats.countdown, 2 * stats.countdown),
+            Countdown::MaidenHealth => Countdown::MaidenHealth,
+        };
+        self.scuffed_chance = stats.scuffed_chance;
     }
 }
 
@@ -26,30 +83,263 @@ fn config_ui(
     mut config: ResMut<Config>,
     mut state: ResMut<State<GameState>>,
     mut current_spawn: ResMut<CurrentSpawn>,
+    mut tick: ResMut<GameTick>,
+    mut share_input: Local<String>,
+    mut share_error: Local<Option<String>>,
+    mut replay_status: Local<Option<Result<String, String>>>,
+    mut leaving: Local<bool>,
 ) {
+    // Only one state change goes through per frame, so a paused wave is left in two steps
+    if *leaving && *state.current() == GameState::Spawned {
+        *leaving = false;
+        state.pop().unwrap();
+    }
+
     egui::Window::new("Config").show(egui_context.ctx_mut(), |ui| {
-        ui.add(egui::Slider::new(&mut config.players, 1..=5).text("Players"));
+        party_ui(ui, &mut config);
+        ui.horizontal(|ui| {
+            for (_, mode) in MODES {
+                if ui.radio(config.mode == *mode, mode.label()).clicked() {
+                    config.set_mode(*mode);
+                }
+            }
+        });
+        ui.checkbox(&mut config.hints, "Show hints");
+        ui.checkbox(&mut config.stacking, "Nylos can stack");
+        ui.add(
+            egui::Slider::new(&mut config.scuffed_chance, 0.0..=0.5)
+                .text("Scuffed spawns")
+                .custom_formatter(|chance, _| format!("{:.0}%", chance * 100.0)),
+        );
+        countdown_ui(ui, &mut config);
+        ui.horizontal(|ui| {
+            let mut seeded = config.seed.is_some();
+            ui.checkbox(&mut seeded, "Seed");
+            match (seeded, config.seed) {
+                (true, None) => config.seed = Some(rand::random::<u32>() as u64),
+                (false, Some(_)) => config.seed = None,
+                _ => {}
+            }
+            if let Some(seed) = config.seed.as_mut() {
+                ui.add(egui::DragValue::new(seed));
+            }
+            if ui.button("Copy link").clicked() {
+                ui.output().copied_text = LaunchOptions::from_setup(&config, &current_spawn).link();
+            }
+        });
         ui.label(format!("Leaks: {}", current_spawn.leaks));
+        ui.label(format!(
+            "Multi-freezes: {} spread, {} on stacks",
+            current_spawn.multi_freezes.spread, current_spawn.multi_freezes.stacked
+        ));
+
+        if !current_spawn.casts().is_empty() {
+            let replay = Replay {
+                scenario: Scenario {
+                    players: config.players,
+                    spawns: current_spawn.spawns().to_vec(),
+                    delays: current_spawn.delays().clone(),
+                    mode: config.mode,
+                },
+                casts: current_spawn.casts().to_vec(),
+            };
+            if ui.button("Save replay").clicked() {
+                *replay_status = Some(save_replay(ui, &replay));
+            }
+        }
+        match &*replay_status {
+            Some(Ok(saved)) => {
+                ui.label(saved);
+            }
+            Some(Err(err)) => {
+                ui.colored_label(egui::Color32::RED, err);
+            }
+            None => {}
+        }
+
+        if !current_spawn.spawns().is_empty() {
+            let scenario = Scenario {
+                players: config.players,
+                spawns: current_spawn.spawns().to_vec(),
+                delays: current_spawn.delays().clone(),
+                mode: config.mode,
+            };
+            ui.horizontal(|ui| {
+                ui.label(format!("Spawn: {}", scenario.notation()));
+                // Read-only, for copying. Codes are loaded through the field below.
+                let code = scenario.to_code();
+                ui.add(egui::TextEdit::singleline(&mut code.as_str()).desired_width(80.0));
+            });
+        }
+
+        ui.horizontal(|ui| {
+            let input = ui.add(
+                egui::TextEdit::singleline(&mut *share_input)
+                    .hint_text("N1 N3 S2 S4w or code")
+                    .desired_width(140.0),
+            );
+            let load = ui.button("Load");
+            let submitted = input.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+
+            if load.clicked() || submitted {
+                match Scenario::parse(&share_input, config.mode) {
+                    Ok(scenario) => {
+                        *share_error = None;
+                        config.players = scenario.players;
+                        config.set_mode(scenario.mode);
+                        current_spawn.load(scenario.spawns, scenario.delays);
+                        leave_wave(&mut state, &mut leaving);
+                    }
+                    Err(err) => *share_error = Some(err),
+                }
+            }
+        });
+        if let Some(err) = &*share_error {
+            ui.colored_label(egui::Color32::RED, err);
+        }
+
         ui.horizontal(|ui| {
             let spawn = ui.button("Spawn");
             let rerun = ui.button("Rerun");
             let reset = ui.button("Reset");
+            let quiz = ui.button("Quiz");
             match state.current() {
                 GameState::Playing => {
                     if spawn.clicked() {
                         state.push(GameState::Spawned).unwrap();
+                    } else if quiz.clicked() {
+                        state.push(GameState::Quiz).unwrap();
                     }
                 }
-                GameState::Spawned => {
+                GameState::Spawned | GameState::Paused => {
                     if reset.clicked() {
-                        state.pop().unwrap();
+                        leave_wave(&mut state, &mut leaving);
                     } else if rerun.clicked() {
                         current_spawn.rerun = true;
-                        state.pop().unwrap();
+                        leave_wave(&mut state, &mut leaving);
                     }
                 }
                 _ => {}
             }
         });
+
+        ui.horizontal(|ui| {
+            let paused = *state.current() == GameState::Paused;
+            let pause = ui.add_enabled(
+                paused || *state.current() == GameState::Spawned,
+                egui::Button::new(if paused { "Resume" } else { "Pause" }),
+            );
+            if pause.on_hover_text("P").clicked() {
+                toggle_pause(&mut state);
+            }
+            let step = ui.add_enabled(paused, egui::Button::new("Step"));
+            if step.on_hover_text(".").clicked() {
+                tick.step = true;
+            }
+        });
+        ui.add(egui::Slider::new(&mut tick.speed, MIN_SPEED..=MAX_SPEED).text("Speed"));
+    });
+}
+
+// Back to the room from a running or paused wave
+fn leave_wave(state: &mut State<GameState>, leaving: &mut bool) {
+    match state.current() {
+        GameState::Spawned => state.pop().unwrap(),
+        GameState::Paused => {
+            state.pop().unwrap();
+            *leaving = true;
+        }
+        _ => {}
+    }
+}
+
+fn party_ui(ui: &mut egui::Ui, config: &mut Config) {
+    let preset = party_preset(config.players);
+    egui::ComboBox::from_label("Party")
+        .selected_text(preset.name)
+        .show_ui(ui, |ui| {
+            for preset in PARTY_PRESETS {
+                ui.selectable_value(&mut config.players, preset.players, preset.name);
+            }
+        });
+    let preset = party_preset(config.players);
+    ui.label(format!(
+        "Maiden {} HP, nylos {} HP, {} spawns, {} teammates",
+        config.mode.hitpoints(preset.maiden_hitpoints),
+        config.mode.hitpoints(preset.nylo_hitpoints),
+        preset.spawns,
+        preset.teammates()
+    ));
+}
+
+fn countdown_ui(ui: &mut egui::Ui, config: &mut Config) {
+    ui.horizontal(|ui| {
+        ui.label("Countdown");
+        let ticks = config.mode.stats().countdown;
+        let options = [
+            ("Fixed", Countdown::Fixed(ticks)),
+            ("Random", Countdown::Random(ticks, 2 * ticks)),
+            ("Maiden HP", Countdown::MaidenHealth),
+        ];
+        for (label, countdown) in options {
+            let selected =
+                std::mem::discriminant(&config.countdown) == std::mem::discriminant(&countdown);
+            if ui.radio(selected, label).clicked() && !selected {
+                config.countdown = countdown;
+            }
+        }
+    });
+    ui.horizontal(|ui| match &mut config.countdown {
+        Countdown::Fixed(ticks) => {
+            ui.add(egui::DragValue::new(ticks).clamp_range(0..=MAX_COUNTDOWN));
+            ui.label("ticks");
+        }
+        Countdown::Random(min, max) => {
+            ui.add(egui::DragValue::new(min).clamp_range(0..=MAX_COUNTDOWN));
+            ui.label("to");
+            ui.add(egui::DragValue::new(max).clamp_range(*min..=MAX_COUNTDOWN));
+            ui.label("ticks");
+        }
+        Countdown::MaidenHealth => {
+            ui.label("Spawns when Maiden reaches 70%");
+        }
     });
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut config.chat_cue, "70% chat message");
+        ui.checkbox(&mut config.health_bar_cue, "Maiden health bar");
+    });
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+// Each replay gets its own file named after the time it was saved, so none are overwritten
+fn save_replay(_ui: &mut egui::Ui, replay: &Replay) -> Result<String, String> {
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let name = format!("replay-{}.txt", seconds);
+    let path = std::env::current_dir()
+        .map(|dir| dir.join(&name))
+        .unwrap_or_else(|_| name.into());
+
+    match std::fs::write(&path, replay.to_text()) {
+        Ok(()) => {
+            info!("Saved replay to {}", path.display());
+            Ok(format!("Saved replay to {}", path.display()))
+        }
+        Err(err) => {
+            error!("Could not save replay to {}: {}", path.display(), err);
+            Err(format!(
+                "Could not save replay to {}: {}",
+                path.display(),
+                err
+            ))
+        }
+    }
+}
+
+// There is no file system on the web, so the replay is copied to the clipboard instead
+#[cfg(target_arch = "wasm32")]
+fn save_replay(ui: &mut egui::Ui, replay: &Replay) -> Result<String, String> {
+    ui.output().copied_text = replay.to_text();
+    Ok("Copied replay to the clipboard".to_string())
 }
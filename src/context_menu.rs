@@ -0,0 +1,230 @@
+use crate::actions::Actions;
+use crate::blood_spawn::BloodSpawnAttacked;
+use crate::cursor::Hovered;
+use crate::matomenos::{Matomenos, NylocasClicked};
+use crate::spawn_point::SpawnPoint;
+use crate::spell::Spell;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+pub struct ContextMenuPlugin;
+
+// How far the mouse can leave the menu before it closes, like the client
+const MENU_MARGIN: f32 = 10.0;
+
+const HEADER_COLOR: egui::Color32 = egui::Color32::from_rgb(93, 84, 71);
+const SPELL_COLOR: egui::Color32 = egui::Color32::from_rgb(0, 255, 0);
+const NPC_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 255, 0);
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MenuAction {
+    Cast(Entity, SpawnPoint),
+    Attack(Entity),
+    WalkHere(f32, f32),
+    Cancel,
+}
+
+impl MenuAction {
+    // The option split into the pieces the client colours differently
+    fn parts(&self) -> Vec<(String, egui::Color32)> {
+        match self {
+            MenuAction::Cast(_, spawn) => vec![
+                ("Cast ".to_string(), egui::Color32::WHITE),
+                ("Ice Barrage".to_string(), SPELL_COLOR),
+                (" -> ".to_string(), egui::Color32::WHITE),
+                (format!("Nylocas Matomenos ({})", spawn.label()), NPC_COLOR),
+            ],
+            MenuAction::Attack(_) => vec![
+                ("Attack ".to_string(), egui::Color32::WHITE),
+                ("Blood spawn".to_string(), NPC_COLOR),
+            ],
+            MenuAction::WalkHere(..) => vec![("Walk here".to_string(), egui::Color32::WHITE)],
+            MenuAction::Cancel => vec![("Cancel".to_string(), egui::Color32::WHITE)],
+        }
+    }
+
+    pub fn layout(&self) -> egui::text::LayoutJob {
+        let mut job = egui::text::LayoutJob::default();
+        for (text, color) in self.parts() {
+            job.append(
+                &text,
+                0.0,
+                egui::TextFormat {
+                    color,
+                    ..Default::default()
+                },
+            );
+        }
+        job
+    }
+}
+
+// The right-click menu, kept exactly as it was when it opened
+#[derive(Default)]
+pub struct ContextMenu {
+    open: Option<(egui::Pos2, Vec<MenuAction>)>,
+}
+
+impl ContextMenu {
+    pub fn is_open(&self) -> bool {
+        self.open.is_some()
+    }
+}
+
+impl Plugin for ContextMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ContextMenu>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(handle_mouse)
+                    .with_system(draw_mouseover),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Spawned)
+                    .with_system(handle_mouse)
+                    .with_system(draw_mouseover),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Paused)
+                    .with_system(handle_mouse)
+                    .with_system(draw_mouseover),
+            );
+    }
+}
+
+// Every option for what's under the cursor, with the left-click option first
+pub fn menu_options(
+    hovered: &Hovered,
+    spell_active: bool,
+    nylos_query: &Query<&Matomenos>,
+) -> Vec<MenuAction> {
+    let mut options = Vec::new();
+    if spell_active {
+        for entity in &hovered.nylos {
+            if let Ok(nylo) = nylos_query.get(*entity) {
+                options.push(MenuAction::Cast(*entity, nylo.spawn()));
+            }
+        }
+    }
+    for entity in &hovered.blood_spawns {
+        options.push(MenuAction::Attack(*entity));
+    }
+    if let Some((x, z)) = hovered.tile {
+        options.push(MenuAction::WalkHere(x, z));
+    }
+    options.push(MenuAction::Cancel);
+    options
+}
+
+fn handle_mouse(
+    mut egui_context: ResMut<EguiContext>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    hovered: Res<Hovered>,
+    mut menu: ResMut<ContextMenu>,
+    mut actions: ResMut<Actions>,
+    mut clicks: EventWriter<NylocasClicked>,
+    mut attacks: EventWriter<BloodSpawnAttacked>,
+    spell_query: Query<(&Spell, &Interaction)>,
+    nylos_query: Query<&Matomenos>,
+) {
+    let ctx = egui_context.ctx_mut();
+    let (spell_active, over_spell) = match spell_query.get_single() {
+        Ok((spell, interaction)) => (spell.is_active, *interaction != Interaction::None),
+        Err(_) => (false, false),
+    };
+
+    let mut chosen = None;
+    let was_open = menu.is_open();
+
+    if let Some((position, options)) = menu.open.clone() {
+        let response = egui::Area::new("context_menu")
+            .order(egui::Order::Foreground)
+            .fixed_pos(position)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(egui::RichText::new("Choose Option").color(HEADER_COLOR));
+                    for option in options {
+                        if ui
+                            .add(egui::Button::new(option.layout()).frame(false))
+                            .clicked()
+                        {
+                            chosen = Some(option);
+                        }
+                    }
+                });
+            })
+            .response;
+
+        let strayed = ctx.input().pointer.hover_pos().is_none_or(|pointer| {
+            !response.rect.expand(MENU_MARGIN).contains(pointer)
+        });
+        if chosen.is_some() || strayed {
+            menu.open = None;
+        }
+    }
+
+    // The spell button and egui windows take their own clicks
+    if hovered.ray.is_some() && !over_spell {
+        if mouse_buttons.just_pressed(MouseButton::Right) {
+            if let Some(pointer) = ctx.input().pointer.hover_pos() {
+                menu.open = Some((pointer, menu_options(&hovered, spell_active, &nylos_query)));
+            }
+        } else if mouse_buttons.just_pressed(MouseButton::Left) && !was_open {
+            chosen = menu_options(&hovered, spell_active, &nylos_query)
+                .into_iter()
+                .next();
+        }
+    }
+
+    match chosen {
+        Some(MenuAction::Cast(entity, _)) => clicks.send(NylocasClicked(entity)),
+        Some(MenuAction::Attack(entity)) => attacks.send(BloodSpawnAttacked(entity)),
+        Some(MenuAction::WalkHere(x, z)) => actions.walk_target = Some(Vec2::new(x, z)),
+        Some(MenuAction::Cancel) | None => {}
+    }
+}
+
+// What a left-click will do, in the top left corner like the client
+fn draw_mouseover(
+    mut egui_context: ResMut<EguiContext>,
+    hovered: Res<Hovered>,
+    menu: Res<ContextMenu>,
+    spell_query: Query<&Spell>,
+    nylos_query: Query<&Matomenos>,
+) {
+    if menu.is_open() || hovered.ray.is_none() {
+        return;
+    }
+
+    let spell_active = spell_query
+        .get_single()
+        .is_ok_and(|spell| spell.is_active);
+    let options = menu_options(&hovered, spell_active, &nylos_query);
+    let mut job = match options.first() {
+        Some(MenuAction::Cancel) | None => return,
+        Some(option) => option.layout(),
+    };
+    let more = options.len() - 1;
+    if more > 0 {
+        job.append(
+            &format!(
+                " / {} more option{}",
+                more,
+                if more == 1 { "" } else { "s" }
+            ),
+            0.0,
+            egui::TextFormat {
+                color: egui::Color32::WHITE,
+                ..Default::default()
+            },
+        );
+    }
+
+    egui::Area::new("mouseover")
+        .anchor(egui::Align2::LEFT_TOP, egui::vec2(8.0, 8.0))
+        .interactable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.label(job);
+        });
+}
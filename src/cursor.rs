@@ -0,0 +1,127 @@
+use crate::blood_spawn::{blood_spawn_hull, BloodSpawn};
+use crate::camera;
+use crate::clickbox::{Clickbox, ConvexHull};
+use crate::matomenos::Matomenos;
+use bevy::prelude::*;
+use bevy_egui::EguiContext;
+
+pub struct CursorPlugin;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+
+impl Ray {
+    pub fn from_cursor(camera: &Camera, transform: &GlobalTransform, cursor: Vec2) -> Option<Ray> {
+        let size = camera.logical_viewport_size()?;
+        let ndc = (cursor / size) * 2.0 - Vec2::ONE;
+        let ndc_to_world = transform.compute_matrix() * camera.projection_matrix().inverse();
+
+        // The projection uses reversed depth, so the near plane sits at 1.0
+        let near = ndc_to_world.project_point3(ndc.extend(1.0));
+        let far = ndc_to_world.project_point3(ndc.extend(0.5));
+
+        Some(Ray {
+            origin: near,
+            direction: (far - near).normalize(),
+        })
+    }
+
+    // Where the ray meets the floor
+    pub fn intersect_floor(&self) -> Option<Vec3> {
+        if self.direction.y.abs() < f32::EPSILON {
+            return None;
+        }
+        let t = -self.origin.y / self.direction.y;
+        (t >= 0.0).then(|| self.origin + self.direction * t)
+    }
+}
+
+// What the mouse is over this frame
+#[derive(Default)]
+pub struct Hovered {
+    pub ray: Option<Ray>,
+    // Every nylo under the cursor, nearest first
+    pub nylos: Vec<Entity>,
+    pub nylo: Option<Entity>,
+    // Every blood spawn under the cursor, nearest first
+    pub blood_spawns: Vec<Entity>,
+    // Centre of the floor tile under the cursor
+    pub tile: Option<(f32, f32)>,
+}
+
+impl Plugin for CursorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Hovered>()
+            .add_system_to_stage(CoreStage::PreUpdate, update_hovered);
+    }
+}
+
+pub fn tile_center(position: Vec3) -> (f32, f32) {
+    (position.x.floor() + 0.5, position.z.floor() + 0.5)
+}
+
+// Every target whose hull the ray passes through, nearest first
+fn nearest_hits(
+    ray: &Ray,
+    hull: &ConvexHull,
+    targets: impl Iterator<Item = (Entity, Vec3)>,
+) -> Vec<Entity> {
+    let mut hits: Vec<(Entity, f32)> = targets
+        .filter_map(|(entity, position)| Some((entity, hull.intersect(ray, position)?)))
+        .collect();
+    hits.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+    hits.into_iter().map(|(entity, _)| entity).collect()
+}
+
+fn update_hovered(
+    windows: Res<Windows>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<camera::Camera>>,
+    nylos_query: Query<(Entity, &Transform, &Matomenos)>,
+    blood_spawns_query: Query<(Entity, &Transform), With<BloodSpawn>>,
+    clickbox: Res<Clickbox>,
+    mut hovered: ResMut<Hovered>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    // The UI sits on top of the room, so nothing under it is hovered
+    let cursor = windows
+        .get_primary()
+        .and_then(|window| window.cursor_position())
+        .filter(|_| !egui_context.ctx_mut().is_pointer_over_area());
+    hovered.ray = match (cursor, camera_query.get_single()) {
+        (Some(cursor), Ok((camera, transform))) => Ray::from_cursor(camera, transform, cursor),
+        _ => None,
+    };
+
+    let ray = match hovered.ray {
+        Some(ray) => ray,
+        None => {
+            hovered.nylos.clear();
+            hovered.nylo = None;
+            hovered.blood_spawns.clear();
+            hovered.tile = None;
+            return;
+        }
+    };
+
+    // Stacked nylos are picked where their models are drawn, not on their shared tile
+    hovered.nylos = nearest_hits(
+        &ray,
+        &clickbox.hull,
+        nylos_query
+            .iter()
+            .map(|(entity, transform, nylo)| (entity, transform.translation + nylo.stack_offset())),
+    );
+    hovered.nylo = hovered.nylos.first().copied();
+
+    hovered.blood_spawns = nearest_hits(
+        &ray,
+        &blood_spawn_hull(),
+        blood_spawns_query
+            .iter()
+            .map(|(entity, transform)| (entity, transform.translation)),
+    );
+    hovered.tile = ray.intersect_floor().map(tile_center);
+}
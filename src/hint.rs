@@ -0,0 +1,152 @@
+use crate::camera;
+use crate::config::Config;
+use crate::matomenos::{BarrageCast, CurrentSpawn, Matomenos};
+use crate::overlay::{self, Projector};
+use crate::player::Player;
+use crate::sim::{tile_in_range, MultiFreezes, SimNylo, Strategy, Wave};
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use std::collections::VecDeque;
+
+pub struct HintPlugin;
+
+// Number of recent casts used to work out the trainee's hit rate
+const HIT_RATE_WINDOW: usize = 20;
+const MIN_CASTS_BEFORE_FADING: usize = 5;
+
+// A cast is a hit when it catches as many nylos as the best target would have
+#[derive(Default)]
+pub struct HintStats {
+    recent: VecDeque<bool>,
+    best_catch: usize,
+}
+
+impl HintStats {
+    pub fn hit_rate(&self) -> Option<f32> {
+        if self.recent.len() < MIN_CASTS_BEFORE_FADING {
+            return None;
+        }
+        let hits = self.recent.iter().filter(|hit| **hit).count();
+        Some(hits as f32 / self.recent.len() as f32)
+    }
+
+    fn opacity(&self) -> f32 {
+        1.0 - self.hit_rate().unwrap_or(0.0)
+    }
+}
+
+impl Plugin for HintPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HintStats>()
+            .add_system_set(SystemSet::on_update(GameState::Spawned).with_system(draw_hint))
+            .add_system_set(SystemSet::on_update(GameState::Paused).with_system(draw_hint));
+    }
+}
+
+fn draw_hint(
+    mut egui_context: ResMut<EguiContext>,
+    mut stats: ResMut<HintStats>,
+    mut casts: EventReader<BarrageCast>,
+    config: Res<Config>,
+    current_spawn: Res<CurrentSpawn>,
+    nylos_query: Query<(Entity, &Transform, &Matomenos)>,
+    player_query: Query<(&Transform, &Player)>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<camera::Camera>>,
+) {
+    for cast in casts.iter() {
+        let hit = cast.caught.len() >= stats.best_catch;
+        stats.recent.push_back(hit);
+        if stats.recent.len() > HIT_RATE_WINDOW {
+            stats.recent.pop_front();
+        }
+    }
+
+    let (player_transform, player) = player_query.single();
+    let (entities, nylos): (Vec<Entity>, Vec<SimNylo>) = nylos_query
+        .iter()
+        .map(|(entity, transform, nylo)| {
+            let sim_nylo = SimNylo {
+                spawn: nylo.spawn(),
+                x: transform.translation.x,
+                z: transform.translation.z,
+                frozen: nylo.frozen(),
+                leaked: false,
+                spawn_tick: 0,
+                hitpoints: nylo.hitpoints(),
+                pending_damage: nylo.pending_damage(),
+                dead: false,
+            };
+            (entity, sim_nylo)
+        })
+        .unzip();
+    let wave = Wave {
+        nylos,
+        tick: current_spawn.tick(),
+        standing: !current_spawn.is_moving(),
+        attack_delay: player.attack_delay,
+        casts: 0,
+        leaks: 0,
+        multi_freezes: MultiFreezes::default(),
+        stacking: config.stacking,
+    };
+
+    // Every unfrozen nylo leaks eventually, so the best target is the biggest catch
+    let target = match Strategy::Greedy.choose(&wave, &mut rand::thread_rng()) {
+        Some(target) => target,
+        None => return,
+    };
+    stats.best_catch = wave.barrage_targets(target).len();
+
+    let opacity = stats.opacity();
+    if !config.hints || opacity <= 0.0 {
+        return;
+    }
+    let projector = match camera_query
+        .get_single()
+        .ok()
+        .and_then(|(camera, transform)| Projector::new(camera, transform))
+    {
+        Some(projector) => projector,
+        None => return,
+    };
+
+    let alpha = (opacity * 255.0) as u8;
+    let painter = overlay::painter(egui_context.ctx_mut(), "hint");
+    let (target_x, target_z) = match nylos_query.get(entities[target]) {
+        Ok((_, transform, _)) => (transform.translation.x, transform.translation.z),
+        Err(_) => return,
+    };
+
+    let highlight = egui::Color32::from_rgba_unmultiplied(255, 215, 0, alpha);
+    if let Some(square) = projector.floor_square(target_x, target_z, 1.5) {
+        painter.add(egui::Shape::closed_line(
+            square,
+            egui::Stroke::new(3.0, highlight),
+        ));
+    }
+    if let Some(center) = projector.project(Vec3::new(target_x, 0.75, target_z)) {
+        painter.circle_stroke(center, 18.0, egui::Stroke::new(2.0, highlight));
+    }
+
+    // Ghost tile for the player, only needed when the target is out of range
+    let player_position = (
+        player_transform.translation.x,
+        player_transform.translation.z,
+    );
+    let (ghost_x, ghost_z) = tile_in_range(player_position, (target_x, target_z));
+    let in_range =
+        (ghost_x - player_position.0).abs() <= 0.5 && (ghost_z - player_position.1).abs() <= 0.5;
+    if !in_range {
+        if let Some(square) = projector.floor_square(ghost_x, ghost_z, 0.5) {
+            painter.add(egui::Shape::convex_polygon(
+                square,
+                egui::Color32::from_rgba_unmultiplied(255, 255, 255, alpha / 3),
+                egui::Stroke::new(
+                    1.5,
+                    egui::Color32::from_rgba_unmultiplied(255, 255, 255, alpha),
+                ),
+            ));
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use crate::matomenos::CurrentSpawn;
+use crate::player::Player;
+use crate::tick::GameTick;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Playing).with_system(draw_tick_indicator),
+        )
+        .add_system_set(SystemSet::on_update(GameState::Spawned).with_system(draw_tick_indicator))
+        .add_system_set(SystemSet::on_update(GameState::Paused).with_system(draw_tick_indicator));
+    }
+}
+
+// A dot that pulses on every tick, with the tick count and the player's attack delay and hitpoints
+fn draw_tick_indicator(
+    mut egui_context: ResMut<EguiContext>,
+    tick: Res<GameTick>,
+    current_spawn: Res<CurrentSpawn>,
+    player_query: Query<&Player>,
+) {
+    let (attack_delay, hitpoints) = match player_query.get_single() {
+        Ok(player) => (player.attack_delay, player.hitpoints),
+        Err(_) => return,
+    };
+
+    egui::Area::new("tick_indicator")
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 8.0))
+        .interactable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                let (rect, _) =
+                    ui.allocate_exact_size(egui::vec2(24.0, 24.0), egui::Sense::hover());
+                let pulse = 1.0 - tick.percent();
+                let color = if attack_delay == 0 {
+                    egui::Color32::from_rgb(80, 220, 80)
+                } else {
+                    egui::Color32::from_rgb(240, 160, 40)
+                };
+                ui.painter()
+                    .circle_filled(rect.center(), 5.0 + 7.0 * pulse, color);
+
+                ui.label(
+                    egui::RichText::new(format!(
+                        "Tick {}   Delay {}   HP {}",
+                        current_spawn.tick(),
+                        attack_delay,
+                        hitpoints
+                    ))
+                    .color(egui::Color32::WHITE)
+                    .strong(),
+                );
+            });
+        });
+}
@@ -0,0 +1,156 @@
+use crate::player::Player;
+use crate::tick::on_game_tick;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+pub struct InventoryPlugin;
+
+// Ticks eating or drinking adds to the attack delay, and before the next bite
+pub const EAT_DELAY: i32 = 3;
+
+pub const SHARK_HEAL: u32 = 20;
+const BREW_DOSES: u32 = 4;
+
+// Drain per tick of a protection prayer, and the resistance with no prayer bonus
+const PROTECTION_DRAIN: u32 = 12;
+const DRAIN_RESISTANCE: u32 = 60;
+
+const INVENTORY_COLUMNS: usize = 4;
+const SLOT_SIZE: egui::Vec2 = egui::vec2(56.0, 32.0);
+
+const PANEL_OFFSET: egui::Vec2 = egui::vec2(-8.0, 84.0);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Item {
+    Shark,
+    // Doses left
+    SaradominBrew(u32),
+}
+
+impl Item {
+    fn label(&self) -> String {
+        match self {
+            Item::Shark => "Shark".to_string(),
+            Item::SaradominBrew(doses) => format!("Brew({})", doses),
+        }
+    }
+
+    // What's left in the slot after one bite or sip
+    pub fn consumed(&self) -> Option<Item> {
+        match self {
+            Item::Shark | Item::SaradominBrew(1) => None,
+            Item::SaradominBrew(doses) => Some(Item::SaradominBrew(doses - 1)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Protection {
+    Magic,
+    Missiles,
+    Melee,
+}
+
+impl Protection {
+    fn label(&self) -> &'static str {
+        match self {
+            Protection::Magic => "Protect from Magic",
+            Protection::Missiles => "Protect from Missiles",
+            Protection::Melee => "Protect from Melee",
+        }
+    }
+}
+
+// A barrage inventory: a few brews and sharks
+pub fn starting_inventory() -> Vec<Option<Item>> {
+    let mut inventory = vec![Some(Item::SaradominBrew(BREW_DOSES)); 4];
+    inventory.extend(vec![Some(Item::Shark); 4]);
+    inventory
+}
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_update(GameState::Playing).with_system(inventory_ui))
+            .add_system_set(SystemSet::on_update(GameState::Spawned).with_system(inventory_ui))
+            .add_system_set(SystemSet::on_update(GameState::Paused).with_system(inventory_ui))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(on_game_tick)
+                    .with_system(drain_prayer),
+            );
+    }
+}
+
+fn drain_prayer(mut player_query: Query<&mut Player>) {
+    for mut player in player_query.iter_mut() {
+        if player.protection.is_none() {
+            continue;
+        }
+        player.prayer_drain += PROTECTION_DRAIN;
+        while player.prayer_drain >= DRAIN_RESISTANCE && player.prayer > 0 {
+            player.prayer_drain -= DRAIN_RESISTANCE;
+            player.prayer -= 1;
+        }
+        if player.prayer == 0 {
+            player.protection = None;
+        }
+    }
+}
+
+// Inventory and protection prayers, under the spell button
+fn inventory_ui(mut egui_context: ResMut<EguiContext>, mut player_query: Query<&mut Player>) {
+    let mut player = match player_query.get_single_mut() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+
+    egui::Area::new("inventory")
+        .anchor(egui::Align2::RIGHT_TOP, PANEL_OFFSET)
+        .show(egui_context.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(format!(
+                    "Hitpoints {}   Prayer {}",
+                    player.hitpoints, player.prayer
+                ));
+                ui.separator();
+
+                let mut eaten = None;
+                egui::Grid::new("inventory_slots").show(ui, |ui| {
+                    for (slot, item) in player.inventory.iter().enumerate() {
+                        let label = item.map_or(String::new(), |item| item.label());
+                        let clicked = ui
+                            .add_enabled_ui(item.is_some(), |ui| {
+                                ui.add_sized(SLOT_SIZE, egui::Button::new(label))
+                            })
+                            .inner
+                            .clicked();
+                        if clicked {
+                            eaten = Some(slot);
+                        }
+                        if slot % INVENTORY_COLUMNS == INVENTORY_COLUMNS - 1 {
+                            ui.end_row();
+                        }
+                    }
+                });
+                if let Some(slot) = eaten {
+                    player.consume(slot);
+                }
+                ui.separator();
+
+                for protection in [Protection::Magic, Protection::Missiles, Protection::Melee] {
+                    let active = player.protection == Some(protection);
+                    if ui
+                        .add_enabled(
+                            player.prayer > 0,
+                            egui::SelectableLabel::new(active, protection.label()),
+                        )
+                        .clicked()
+                    {
+                        // Only one protection prayer can be up at a time
+                        player.protection = if active { None } else { Some(protection) };
+                    }
+                }
+            });
+        });
+}
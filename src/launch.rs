@@ -0,0 +1,307 @@
+use crate::config::Config;
+use crate::matomenos::CurrentSpawn;
+use crate::mode::Mode;
+use crate::replay::{Replay, ReplayPlayback};
+use crate::scenario::{Scenario, MAX_PLAYERS};
+use bevy::prelude::*;
+
+pub struct LaunchPlugin;
+
+#[cfg(not(target_arch = "wasm32"))]
+const PAGE_URL: &str = "https://infernostats.github.io/maiden_sim/";
+
+#[cfg(not(target_arch = "wasm32"))]
+pub const USAGE: &str = "Usage: maiden [OPTIONS]
+
+Options:
+    --seed <SEED>        Generate waves from a fixed seed
+    --players <1-5>      Party size
+    --mode <MODE>        entry, normal or hard
+    --scenario <FILE>    Load a spawn notation or share code from a file
+    --replay <FILE>      Load a recorded wave and play back its casts
+    --skip-menu          Start in the room instead of the main menu
+    --windowed           Run in a window (default)
+    --fullscreen         Run in borderless fullscreen
+    --help               Print this message";
+
+// Startup settings for a specific drill, read from the command line or the page URL
+#[derive(Clone, Debug, Default)]
+pub struct LaunchOptions {
+    pub seed: Option<u64>,
+    pub players: Option<usize>,
+    pub mode: Option<Mode>,
+    pub scenario: Option<Scenario>,
+    pub replay: Option<Replay>,
+    pub skip_menu: bool,
+    pub fullscreen: bool,
+}
+
+impl Plugin for LaunchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LaunchOptions>()
+            .add_startup_system(apply_launch_options);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl LaunchOptions {
+    pub fn from_args<I: Iterator<Item = String>>(mut args: I) -> Result<LaunchOptions, String> {
+        let mut options = LaunchOptions::default();
+
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| format!("{} requires a value", name))
+            };
+
+            match arg.as_str() {
+                "--seed" => {
+                    let seed = value("--seed")?;
+                    options.seed = Some(
+                        seed.parse()
+                            .map_err(|_| format!("Invalid seed \"{}\"", seed))?,
+                    );
+                }
+                "--players" => {
+                    let players = value("--players")?;
+                    options.players = Some(
+                        players
+                            .parse::<usize>()
+                            .ok()
+                            .filter(|p| (1..=MAX_PLAYERS).contains(p))
+                            .ok_or_else(|| format!("Invalid player count \"{}\"", players))?,
+                    );
+                }
+                "--mode" => {
+                    let mode = value("--mode")?;
+                    options.mode = Some(
+                        Mode::from_name(&mode)
+                            .ok_or_else(|| format!("Invalid mode \"{}\"", mode))?,
+                    );
+                }
+                "--scenario" => {
+                    let path = value("--scenario")?;
+                    options.scenario = Some(Scenario::parse(&read_file(&path)?, Mode::Normal)?);
+                }
+                "--replay" => {
+                    let path = value("--replay")?;
+                    options.replay = Some(Replay::parse(&read_file(&path)?)?);
+                }
+                "--skip-menu" => options.skip_menu = true,
+                "--windowed" => options.fullscreen = false,
+                "--fullscreen" => options.fullscreen = true,
+                _ => return Err(format!("Unknown option \"{}\"", arg)),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+// Undoes form encoding, where a space can be "+" or "%20"
+fn percent_decode(value: &str) -> Option<String> {
+    let mut bytes = vec![];
+    let mut rest = value.bytes();
+
+    while let Some(byte) = rest.next() {
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = [rest.next()?, rest.next()?];
+                let hex = std::str::from_utf8(&hex).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            }
+            _ => bytes.push(byte),
+        }
+    }
+
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_file(path: &str) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|err| format!("Could not read {}: {}", path, err))
+}
+
+impl LaunchOptions {
+    // Parses "seed=1&players=3&mode=hard&scenario=AQJFAg", ignoring unknown or malformed
+    // keys. A later value for a key only replaces an earlier one when it parses.
+    pub fn from_query(query: &str) -> LaunchOptions {
+        let mut options = LaunchOptions::default();
+
+        for pair in query.trim_start_matches(['?', '#']).split('&') {
+            let (key, value) = match pair.split_once('=') {
+                Some((key, value)) => match percent_decode(value) {
+                    Some(value) => (key, value),
+                    None => continue,
+                },
+                None => continue,
+            };
+
+            match key {
+                "seed" => {
+                    if let Ok(seed) = value.parse() {
+                        options.seed = Some(seed);
+                    }
+                }
+                "players" => {
+                    if let Some(players) = value
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|p| (1..=MAX_PLAYERS).contains(p))
+                    {
+                        options.players = Some(players);
+                    }
+                }
+                "mode" => {
+                    if let Some(mode) = Mode::from_name(&value) {
+                        options.mode = Some(mode);
+                    }
+                }
+                "scenario" => {
+                    if let Ok(scenario) = Scenario::parse(&value, Mode::Normal) {
+                        options.scenario = Some(scenario);
+                    }
+                }
+                _ => warn!("Ignoring unknown launch option \"{}\"", key),
+            }
+        }
+
+        options
+    }
+
+    pub fn to_query(&self) -> String {
+        let mut pairs = vec![];
+
+        if let Some(seed) = self.seed {
+            pairs.push(format!("seed={}", seed));
+        }
+        if let Some(players) = self.players {
+            pairs.push(format!("players={}", players));
+        }
+        if let Some(mode) = self.mode {
+            pairs.push(format!("mode={}", mode.name()));
+        }
+        if let Some(scenario) = &self.scenario {
+            pairs.push(format!("scenario={}", scenario.to_code()));
+        }
+
+        pairs.join("&")
+    }
+
+    pub fn from_setup(config: &Config, current_spawn: &CurrentSpawn) -> LaunchOptions {
+        let scenario = if current_spawn.spawns().is_empty() {
+            None
+        } else {
+            Some(Scenario {
+                players: config.players,
+                spawns: current_spawn.spawns().to_vec(),
+                delays: current_spawn.delays().clone(),
+                mode: config.mode,
+            })
+        };
+
+        LaunchOptions {
+            seed: config.seed,
+            players: Some(config.players),
+            mode: Some(config.mode),
+            scenario,
+            ..Default::default()
+        }
+    }
+
+    // Both the query string and the fragment are read, with the fragment taking precedence
+    #[cfg(target_arch = "wasm32")]
+    pub fn from_page_url() -> LaunchOptions {
+        let location = match web_sys::window() {
+            Some(window) => window.location(),
+            None => return LaunchOptions::default(),
+        };
+
+        let search = location.search().unwrap_or_default();
+        let hash = location.hash().unwrap_or_default();
+        LaunchOptions::from_query(&format!("{}&{}", search, hash.trim_start_matches('#')))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn link(&self) -> String {
+        let location = web_sys::window().unwrap().location();
+        let origin = location.origin().unwrap_or_default();
+        let path = location.pathname().unwrap_or_default();
+        format!("{}{}?{}", origin, path, self.to_query())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn link(&self) -> String {
+        format!("{}?{}", PAGE_URL, self.to_query())
+    }
+}
+
+fn apply_launch_options(
+    options: Res<LaunchOptions>,
+    mut config: ResMut<Config>,
+    mut current_spawn: ResMut<CurrentSpawn>,
+    mut playback: ResMut<ReplayPlayback>,
+) {
+    if let Some(seed) = options.seed {
+        config.seed = Some(seed);
+    }
+    if let Some(players) = options.players {
+        config.players = players;
+    }
+    if let Some(mode) = options.mode {
+        config.set_mode(mode);
+    }
+    // Notation is parsed as normal mode, so an explicit mode wins over the scenario's
+    if let Some(scenario) = &options.scenario {
+        if options.players.is_none() {
+            config.players = scenario.players;
+        }
+        if options.mode.is_none() {
+            config.set_mode(scenario.mode);
+        }
+        current_spawn.load(scenario.spawns.clone(), scenario.delays.clone());
+    }
+    if let Some(replay) = &options.replay {
+        if options.players.is_none() {
+            config.players = replay.scenario.players;
+        }
+        // Playback only runs at the replay's mode, so an explicit mode changes the replay's
+        let mut replay = replay.clone();
+        match options.mode {
+            Some(mode) => replay.scenario.mode = mode,
+            None => config.set_mode(replay.scenario.mode),
+        }
+        current_spawn.load(
+            replay.scenario.spawns.clone(),
+            replay.scenario.delays.clone(),
+        );
+        playback.replay = Some(replay);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_escaped_notation() {
+        let options = LaunchOptions::from_query("?scenario=N4w+S1%2B2%20S3");
+        assert_eq!(
+            options.scenario,
+            Some(Scenario::parse("N4w S1+2 S3", Mode::Normal).unwrap())
+        );
+        assert!(LaunchOptions::from_query("scenario=N1%2")
+            .scenario
+            .is_none());
+    }
+
+    #[test]
+    fn fragment_only_overrides_with_values_that_parse() {
+        let options =
+            LaunchOptions::from_query("?seed=7&players=3&mode=hard&seed=x&players=9&mode=&seed=8");
+        assert_eq!(options.seed, Some(8));
+        assert_eq!(options.players, Some(3));
+        assert_eq!(options.mode, Some(Mode::Hard));
+    }
+}
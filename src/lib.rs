@@ -1,36 +1,89 @@
+// Bevy systems take their resources and queries as arguments, so long argument
+// lists and nested query types are normal here
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
 mod actions;
+mod animation;
+mod billboard;
+mod blood;
+mod blood_spawn;
 mod camera;
+mod chat;
+mod clickbox;
 mod config;
+mod context_menu;
+mod cursor;
+mod hint;
+mod hud;
+mod inventory;
+mod launch;
 mod loading;
 mod maiden;
 mod map;
 mod matomenos;
 mod menu;
+pub mod mode;
+mod overlay;
+pub mod party;
 mod player;
-mod spawn_point;
+mod quiz;
+mod replay;
+mod rewind;
+pub mod scenario;
+pub mod sim;
+mod solver;
+mod sound;
+pub mod spawn_point;
 mod spell;
+mod tick;
 
 use crate::actions::ActionsPlugin;
+use crate::animation::WalkAnimationPlugin;
+use crate::billboard::BillboardPlugin;
+use crate::blood::BloodPlugin;
+use crate::blood_spawn::BloodSpawnPlugin;
 use crate::camera::CameraPlugin;
+use crate::chat::ChatPlugin;
+use crate::clickbox::ClickboxPlugin;
 use crate::config::ConfigPlugin;
+use crate::context_menu::ContextMenuPlugin;
+use crate::cursor::CursorPlugin;
+use crate::hint::HintPlugin;
+use crate::hud::HudPlugin;
+use crate::inventory::InventoryPlugin;
+use crate::launch::LaunchPlugin;
 use crate::loading::LoadingPlugin;
 use crate::maiden::MaidenPlugin;
 use crate::map::MapPlugin;
 use crate::matomenos::MatomenosPlugin;
 use crate::menu::MenuPlugin;
+use crate::overlay::OverlayPlugin;
 use crate::player::PlayerPlugin;
+use crate::quiz::QuizPlugin;
+use crate::replay::ReplayPlugin;
+use crate::rewind::RewindPlugin;
+use crate::solver::SolverPlugin;
+use crate::sound::SoundPlugin;
 use crate::spell::SpellPlugin;
+use crate::tick::TickPlugin;
 
 use bevy::app::App;
 #[cfg(debug_assertions)]
 use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
 use bevy::prelude::*;
 
+pub use crate::launch::LaunchOptions;
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::launch::USAGE;
+
 #[derive(Clone, Eq, PartialEq, Debug, Hash)]
 enum GameState {
     Loading,
     Playing,
     Spawned,
+    Paused,
+    // Reading spawn patterns instead of freezing them
+    Quiz,
     Menu,
 }
 
@@ -39,20 +92,39 @@ pub struct GamePlugin;
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.add_state(GameState::Loading)
+            .add_plugin(LaunchPlugin)
             .add_plugin(LoadingPlugin)
+            .add_plugin(TickPlugin)
             .add_plugin(ConfigPlugin)
             .add_plugin(MenuPlugin)
             .add_plugin(MapPlugin)
             .add_plugin(MaidenPlugin)
             .add_plugin(ActionsPlugin)
             .add_plugin(PlayerPlugin)
+            .add_plugin(WalkAnimationPlugin)
             .add_plugin(MatomenosPlugin)
+            .add_plugin(BloodPlugin)
+            .add_plugin(BloodSpawnPlugin)
             .add_plugin(SpellPlugin)
+            .add_plugin(ReplayPlugin)
+            .add_plugin(SolverPlugin)
+            .add_plugin(HintPlugin)
+            .add_plugin(QuizPlugin)
+            .add_plugin(RewindPlugin)
+            .add_plugin(SoundPlugin)
+            .add_plugin(HudPlugin)
+            .add_plugin(ChatPlugin)
+            .add_plugin(InventoryPlugin)
+            .add_plugin(ClickboxPlugin)
+            .add_plugin(CursorPlugin)
+            .add_plugin(ContextMenuPlugin)
+            .add_plugin(OverlayPlugin)
+            .add_plugin(BillboardPlugin)
             .add_plugin(CameraPlugin);
 
         #[cfg(debug_assertions)]
         {
-            app.add_plugin(FrameTimeDiagnosticsPlugin::default())
+            app.add_plugin(FrameTimeDiagnosticsPlugin)
                 .add_plugin(LogDiagnosticsPlugin::default());
         }
     }
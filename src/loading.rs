@@ -1,4 +1,6 @@
+use crate::launch::LaunchOptions;
 use crate::GameState;
+use bevy::gltf::Gltf;
 use bevy::prelude::*;
 use bevy_asset_loader::prelude::*;
 
@@ -6,12 +8,18 @@ pub struct LoadingPlugin;
 
 impl Plugin for LoadingPlugin {
     fn build(&self, app: &mut App) {
+        let next_state = match app.world.get_resource::<LaunchOptions>() {
+            Some(options) if options.skip_menu => GameState::Playing,
+            _ => GameState::Menu,
+        };
+
         app.add_loading_state(
             LoadingState::new(GameState::Loading)
                 .with_collection::<FontAssets>()
                 .with_collection::<ImageAssets>()
+                .with_collection::<AudioAssets>()
                 .with_collection::<ModelAssets>()
-                .continue_to_state(GameState::Menu),
+                .continue_to_state(next_state),
         );
     }
 }
@@ -22,10 +30,16 @@ pub struct ModelAssets {
     pub room_model: Handle<Scene>,
     #[asset(path = "models/player.glb#Scene0")]
     pub player_model: Handle<Scene>,
+    #[asset(path = "models/player.glb")]
+    pub player_gltf: Handle<Gltf>,
     #[asset(path = "models/maiden.glb#Scene0")]
     pub maiden_model: Handle<Scene>,
     #[asset(path = "models/matomenos.glb#Scene0")]
     pub matomenos_model: Handle<Scene>,
+    #[asset(path = "models/matomenos.glb")]
+    pub matomenos_gltf: Handle<Gltf>,
+    #[asset(path = "models/matomenos.glb#Mesh0/Primitive0")]
+    pub matomenos_mesh: Handle<Mesh>,
 }
 
 #[derive(AssetCollection)]
@@ -41,3 +55,17 @@ pub struct ImageAssets {
     #[asset(path = "images/ice_barrage_selected.png")]
     pub spell_selected_sprite: Handle<Image>,
 }
+
+#[derive(AssetCollection)]
+pub struct AudioAssets {
+    #[asset(path = "sounds/tick.wav")]
+    pub tick: Handle<AudioSource>,
+    #[asset(path = "sounds/spawn.wav")]
+    pub spawn: Handle<AudioSource>,
+    #[asset(path = "sounds/cast.wav")]
+    pub cast: Handle<AudioSource>,
+    #[asset(path = "sounds/freeze.wav")]
+    pub freeze: Handle<AudioSource>,
+    #[asset(path = "sounds/leak.wav")]
+    pub leak: Handle<AudioSource>,
+}
@@ -1,15 +1,52 @@
+use crate::config::{Config, Countdown};
 use crate::loading::ModelAssets;
+use crate::matomenos::{CurrentSpawn, MoveNylos, SpawnNylos};
+use crate::party::party_preset;
+use crate::tick::on_game_tick;
 use crate::GameState;
 use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use rand::Rng;
 
 pub struct MaidenPlugin;
 
+// Maiden's health when the wave comes up, and the chat cue for it
+const SPAWN_THRESHOLD: f32 = 70.0;
+pub const MAIDEN_CUE: &str = "70%";
+
+// With a tick countdown Maiden loses this much health a tick, so the bar lines up with it
+const COUNTDOWN_DRAIN: f32 = 1.0;
+
+const HEALTH_BAR_SIZE: egui::Vec2 = egui::vec2(240.0, 14.0);
+
 #[derive(Component)]
 pub struct Maiden;
 
+// Maiden's simulated health in percent, which only matters before the wave comes up
+pub struct MaidenHealth {
+    percent: f32,
+}
+
+impl Default for MaidenHealth {
+    fn default() -> MaidenHealth {
+        MaidenHealth { percent: 100.0 }
+    }
+}
+
 impl Plugin for MaidenPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(SystemSet::on_enter(GameState::Playing).with_system(spawn_maiden));
+        app.init_resource::<MaidenHealth>()
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(spawn_maiden))
+            .add_system_set(
+                SystemSet::on_enter(GameState::Spawned).with_system(start_maiden_health),
+            )
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(on_game_tick)
+                    .with_system(drain_maiden_health.after(MoveNylos).before(SpawnNylos)),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Spawned).with_system(draw_health_bar))
+            .add_system_set(SystemSet::on_update(GameState::Paused).with_system(draw_health_bar));
     }
 }
 
@@ -29,3 +66,83 @@ fn spawn_maiden(mut commands: Commands, models: Res<ModelAssets>) {
         .insert(Name::new("Maiden"))
         .insert(Maiden);
 }
+
+// With the health countdown she starts a little above the threshold and loses a
+// random amount each tick, both depending on the difficulty
+fn start_maiden_health(mut health: ResMut<MaidenHealth>, config: Res<Config>) {
+    let (min, max) = config.mode.stats().health_headroom;
+    health.percent = SPAWN_THRESHOLD + rand::thread_rng().gen_range(min..=max);
+}
+
+fn drain_maiden_health(
+    config: Res<Config>,
+    state: Res<State<GameState>>,
+    mut health: ResMut<MaidenHealth>,
+    mut current_spawn: ResMut<CurrentSpawn>,
+) {
+    // Once the threshold is reached the wave is a tick away, so the drain stops
+    let counting_down = matches!(state.current(), GameState::Spawned | GameState::Paused)
+        && current_spawn.countdown().is_none()
+        && !current_spawn.is_spawned();
+    if config.countdown != Countdown::MaidenHealth || !counting_down {
+        return;
+    }
+
+    let (min, max) = config.mode.stats().health_drain;
+    health.percent -= rand::thread_rng().gen_range(min..=max);
+    if health.percent <= SPAWN_THRESHOLD {
+        health.percent = SPAWN_THRESHOLD;
+        current_spawn.start();
+    }
+}
+
+// Boss health bar with the spawn threshold marked, like the bar players watch in game
+fn draw_health_bar(
+    mut egui_context: ResMut<EguiContext>,
+    config: Res<Config>,
+    health: Res<MaidenHealth>,
+    current_spawn: Res<CurrentSpawn>,
+) {
+    if !config.health_bar_cue {
+        return;
+    }
+    let percent = match (current_spawn.is_spawned(), current_spawn.countdown()) {
+        (true, _) => SPAWN_THRESHOLD,
+        // The threshold is reached on the last tick, along with the chat cue
+        (false, Some(ticks)) => SPAWN_THRESHOLD + ticks.saturating_sub(1) as f32 * COUNTDOWN_DRAIN,
+        (false, None) => health.percent,
+    };
+
+    let max_hitpoints = config
+        .mode
+        .hitpoints(party_preset(config.players).maiden_hitpoints);
+    let hitpoints = (max_hitpoints as f32 * percent / 100.0).round() as u32;
+
+    egui::Area::new("maiden_health")
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 40.0))
+        .interactable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            let (bar, _) = ui.allocate_exact_size(HEALTH_BAR_SIZE, egui::Sense::hover());
+            let mut remaining = bar;
+            remaining.set_width(bar.width() * percent / 100.0);
+            let threshold = bar.left() + bar.width() * SPAWN_THRESHOLD / 100.0;
+
+            let painter = ui.painter();
+            painter.rect_filled(bar, 0.0, egui::Color32::from_rgb(120, 0, 0));
+            painter.rect_filled(remaining, 0.0, egui::Color32::from_rgb(0, 160, 0));
+            painter.line_segment(
+                [
+                    egui::pos2(threshold, bar.top()),
+                    egui::pos2(threshold, bar.bottom()),
+                ],
+                egui::Stroke::new(2.0, egui::Color32::YELLOW),
+            );
+            painter.text(
+                bar.center(),
+                egui::Align2::CENTER_CENTER,
+                format!("{} / {}  ({:.0}%)", hitpoints, max_hitpoints, percent),
+                egui::FontId::proportional(12.0),
+                egui::Color32::WHITE,
+            );
+        });
+}
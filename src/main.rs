@@ -1,14 +1,11 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use bevy::prelude::{
-    AmbientLight, App, ClearColor, Color, NonSend, StandardMaterial, WindowDescriptor,
-};
-use bevy::window::WindowId;
+use bevy::prelude::{AmbientLight, App, ClearColor, Color, NonSend, WindowDescriptor};
+use bevy::window::{WindowId, WindowMode};
 use bevy::winit::WinitWindows;
 use bevy::DefaultPlugins;
 use bevy_egui::EguiPlugin;
-use bevy_mod_picking::prelude::*;
-use maiden::GamePlugin;
+use maiden::{GamePlugin, LaunchOptions};
 use std::io::Cursor;
 use winit::window::Icon;
 
@@ -16,6 +13,13 @@ pub const WINDOW_HEIGHT: f32 = 540.0;
 pub const WINDOW_WIDTH: f32 = 960.0;
 
 fn main() {
+    let options = launch_options();
+    let mode = if options.fullscreen {
+        WindowMode::BorderlessFullscreen
+    } else {
+        WindowMode::Windowed
+    };
+
     App::new()
         .insert_resource(ClearColor(Color::rgb(0.2, 0.2, 0.2)))
         .insert_resource(WindowDescriptor {
@@ -23,22 +27,41 @@ fn main() {
             height: WINDOW_HEIGHT,
             title: "Maiden Simulator".to_string(),
             canvas: Some("#maiden".to_owned()),
+            mode,
             ..Default::default()
         })
+        .insert_resource(options)
         .insert_resource(AmbientLight {
             color: Color::WHITE,
             brightness: 0.8 / 1.0f32,
         })
         .add_plugins(DefaultPlugins)
-        .add_plugins_with(DefaultPickingPlugins::build(RaycastBackend), |group| {
-            group.disable::<CustomHighlightingPlugin<StandardMaterial>>()
-        })
         .add_plugin(EguiPlugin)
         .add_plugin(GamePlugin)
         .add_startup_system(set_window_icon)
         .run();
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+fn launch_options() -> LaunchOptions {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", maiden::USAGE);
+        std::process::exit(0);
+    }
+
+    LaunchOptions::from_args(args.into_iter()).unwrap_or_else(|err| {
+        eprintln!("{}\n\n{}", err, maiden::USAGE);
+        std::process::exit(2);
+    })
+}
+
+// The web build reads its options from the page URL instead
+#[cfg(target_arch = "wasm32")]
+fn launch_options() -> LaunchOptions {
+    LaunchOptions::from_page_url()
+}
+
 // Sets the icon on windows and X11
 fn set_window_icon(windows: NonSend<WinitWindows>) {
     let primary = windows.get_window(WindowId::primary()).unwrap();
@@ -1,28 +1,131 @@
-use crate::config::Config;
+use crate::animation::Walker;
+use crate::billboard::Hitsplats;
+use crate::chat::{teammate_callouts, Chat};
+use crate::clickbox::Clickbox;
+use crate::config::{Config, Countdown};
 use crate::loading::ModelAssets;
-use crate::map::MAP_WIDTH;
+use crate::maiden::MAIDEN_CUE;
+use crate::party::party_preset;
 use crate::player::Player;
-use crate::spawn_point::{generate_spawn_points, SpawnPoint};
+use crate::replay::ReplayCast;
+use crate::sim::{
+    can_enter, in_barrage_area, step_nylo, BarrageHit, FrozenState, MultiFreezes, NyloStep,
+    CAST_DELAY,
+};
+use crate::spawn_point::{
+    format_delayed_spawns, generate_spawn_points, scuff_spawns, wave_rng, SpawnDelays, SpawnPoint,
+};
 use crate::spell::Spell;
+use crate::tick::{on_wave_tick, GameTick};
 use crate::GameState;
-use bevy::{pbr::NotShadowCaster, prelude::*, time::FixedTimestep};
-use bevy_mod_picking::prelude::*;
+use bevy::{pbr::NotShadowCaster, prelude::*};
+use rand::Rng;
+use std::time::Duration;
 
 pub struct MatomenosPlugin;
 
-enum FrozenState {
-    NotFrozen,
-    ShouldFreeze,
-    Frozen,
-}
+// Label for the tick system that walks the nylos, for systems that need to see a tick before it
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MoveNylos;
+
+// Label for the tick system that brings up the wave and its delayed spawns, after the nylos walk
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpawnNylos;
+
+// How far stacked models are spread around their shared tile
+const STACK_SPREAD: f32 = 0.25;
+
+// The clickbox mesh tinted when the nylo is frozen, kept on a child so it sits on the model
+#[derive(Component)]
+struct FreezeOverlay;
 
 #[derive(Component)]
 pub struct Matomenos {
     frozen: FrozenState,
     color_timer: Timer,
     color_handle: Handle<StandardMaterial>,
+    spawn: SpawnPoint,
+    hitpoints: u32,
+    max_hitpoints: u32,
+    // A barrage hit or splash that lands on the next tick
+    pending_hit: Option<BarrageHit>,
+    // Where the nylo stood before the last tick, for drawing it walking to its tile
+    previous: Vec3,
+    // Where the model is drawn relative to its tile, fanned out from any nylos sharing it
+    stack_offset: Vec3,
+    // How far behind its tile the model is drawn while it walks there over the tick
+    lag: Vec3,
 }
 
+impl Matomenos {
+    pub fn spawn(&self) -> SpawnPoint {
+        self.spawn
+    }
+
+    pub fn frozen(&self) -> FrozenState {
+        self.frozen
+    }
+
+    pub fn hitpoints(&self) -> u32 {
+        self.hitpoints
+    }
+
+    pub fn max_hitpoints(&self) -> u32 {
+        self.max_hitpoints
+    }
+
+    pub fn pending_damage(&self) -> Option<u32> {
+        self.pending_hit.map(|hit| hit.damage())
+    }
+
+    pub fn stack_offset(&self) -> Vec3 {
+        self.stack_offset
+    }
+
+    pub fn lag(&self) -> Vec3 {
+        self.lag
+    }
+
+    pub fn state(&self, transform: &Transform) -> NyloState {
+        NyloState {
+            spawn: self.spawn,
+            translation: transform.translation,
+            frozen: self.frozen,
+            color_elapsed: self.color_timer.elapsed(),
+            hitpoints: self.hitpoints,
+            max_hitpoints: self.max_hitpoints,
+            pending_hit: self.pending_hit,
+        }
+    }
+}
+
+// Everything needed to put a nylo back exactly as it was
+#[derive(Clone)]
+pub struct NyloState {
+    spawn: SpawnPoint,
+    translation: Vec3,
+    frozen: FrozenState,
+    color_elapsed: Duration,
+    hitpoints: u32,
+    max_hitpoints: u32,
+    pending_hit: Option<BarrageHit>,
+}
+
+impl NyloState {
+    fn new(spawn: SpawnPoint, hitpoints: u32) -> NyloState {
+        NyloState {
+            spawn,
+            translation: Vec3::new(spawn.x, 0.75, spawn.y),
+            frozen: FrozenState::NotFrozen,
+            color_elapsed: Duration::ZERO,
+            hitpoints,
+            max_hitpoints: hitpoints,
+            pending_hit: None,
+        }
+    }
+}
+
+#[derive(Clone)]
 enum ActionState {
     NotSpawned,
     Spawned,
@@ -30,135 +133,323 @@ enum ActionState {
 }
 
 pub struct CurrentSpawn {
-    spawn_delay: Timer,
+    // Ticks left until the wave comes up, or None while waiting on Maiden's health
+    countdown: Option<u32>,
     spawns: Vec<SpawnPoint>,
+    delays: SpawnDelays,
+    // Delayed spawns that haven't come up yet
+    pending: Vec<SpawnPoint>,
     state: ActionState,
+    wave: u64,
+    tick: u32,
+    casts: Vec<ReplayCast>,
     pub leaks: i32,
+    pub multi_freezes: MultiFreezes,
     pub rerun: bool,
+    pub queued: bool,
 }
 
 impl Default for CurrentSpawn {
     fn default() -> CurrentSpawn {
         CurrentSpawn {
-            spawn_delay: Timer::from_seconds(3.0, false),
+            countdown: None,
             spawns: Vec::new(),
+            delays: SpawnDelays::default(),
+            pending: Vec::new(),
             state: ActionState::NotSpawned,
+            wave: 0,
+            tick: 0,
+            casts: Vec::new(),
             leaks: 0,
+            multi_freezes: MultiFreezes::default(),
             rerun: false,
+            queued: false,
         }
     }
 }
 
+// The parts of CurrentSpawn that change from tick to tick during a wave
+#[derive(Clone)]
+pub struct SpawnState {
+    state: ActionState,
+    pending: Vec<SpawnPoint>,
+    tick: u32,
+    casts: Vec<ReplayCast>,
+    leaks: i32,
+    multi_freezes: MultiFreezes,
+}
+
+impl CurrentSpawn {
+    pub fn spawns(&self) -> &[SpawnPoint] {
+        &self.spawns
+    }
+
+    pub fn delays(&self) -> &SpawnDelays {
+        &self.delays
+    }
+
+    pub fn countdown(&self) -> Option<u32> {
+        self.countdown
+    }
+
+    // Brings the wave up on the next tick, once Maiden's health reaches the threshold
+    pub fn start(&mut self) {
+        self.countdown = Some(1);
+    }
+
+    // Number of game ticks since the nylos started moving
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    pub fn casts(&self) -> &[ReplayCast] {
+        &self.casts
+    }
+
+    // Whether the wave has taken its first step, after standing still for a tick
+    pub fn is_moving(&self) -> bool {
+        matches!(self.state, ActionState::Moving)
+    }
+
+    pub fn is_spawned(&self) -> bool {
+        !matches!(self.state, ActionState::NotSpawned)
+    }
+
+    pub fn state(&self) -> SpawnState {
+        SpawnState {
+            state: self.state.clone(),
+            pending: self.pending.clone(),
+            tick: self.tick,
+            casts: self.casts.clone(),
+            leaks: self.leaks,
+            multi_freezes: self.multi_freezes,
+        }
+    }
+
+    pub fn restore(&mut self, state: &SpawnState) {
+        self.state = state.state.clone();
+        self.pending = state.pending.clone();
+        self.tick = state.tick;
+        self.casts = state.casts.clone();
+        self.leaks = state.leaks;
+        self.multi_freezes = state.multi_freezes;
+    }
+
+    // Replaces the next wave with the given spawns and starts it as soon as the room is reset
+    pub fn load(&mut self, spawns: Vec<SpawnPoint>, delays: SpawnDelays) {
+        self.spawns = spawns;
+        self.delays = delays;
+        self.rerun = true;
+        self.queued = true;
+    }
+}
+
 impl Plugin for MatomenosPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<CurrentSpawn>()
             .add_event::<NylocasClicked>()
+            .add_event::<BarrageCast>()
             .add_system_set(
                 SystemSet::on_update(GameState::Spawned)
-                    .with_system(spawn_nylos)
                     .with_system(draw_freeze)
+                    .with_system(interpolate_nylos)
                     .with_system(NylocasClicked::handle_events),
             )
             .add_system_set(
-                SystemSet::on_update(GameState::Spawned)
-                    .with_run_criteria(FixedTimestep::step(0.6))
-                    .with_system(move_nylos),
+                SystemSet::on_update(GameState::Paused)
+                    .with_system(NylocasClicked::handle_events)
+                    .with_system(interpolate_nylos),
             )
-            .add_system_set(SystemSet::on_resume(GameState::Playing).with_system(reset));
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(on_wave_tick)
+                    .with_system(move_nylos.label(MoveNylos))
+                    .with_system(spawn_nylos.label(SpawnNylos).after(MoveNylos)),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing).with_system(start_queued_spawn),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Spawned).with_system(start_countdown))
+            .add_system_set(SystemSet::on_exit(GameState::Spawned).with_system(reset));
     }
 }
 
 fn spawn_nylos(
-    time: Res<Time>,
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
+    clickbox: Res<Clickbox>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut current_spawn: ResMut<CurrentSpawn>,
     models: Res<ModelAssets>,
     config: ResMut<Config>,
+    mut chat: ResMut<Chat>,
 ) {
-    match current_spawn.state {
-        ActionState::Spawned | ActionState::Moving => return,
-        ActionState::NotSpawned => (),
-    }
-
-    if current_spawn.spawn_delay.tick(time.delta()).finished() {
-        if !current_spawn.spawns.is_empty() {
-            for spawn_point in &current_spawn.spawns {
-                spawn_single_nylo(
-                    &mut commands,
-                    &mut meshes,
-                    &mut materials,
-                    &models,
-                    *spawn_point,
-                );
-            }
-        } else {
-            let spawn_points = generate_spawn_points(2 * config.players);
-
-            for spawn_point in spawn_points {
-                current_spawn.spawns.push(spawn_point);
-                spawn_single_nylo(
-                    &mut commands,
-                    &mut meshes,
-                    &mut materials,
-                    &models,
-                    spawn_point,
-                );
-            }
+    if let ActionState::NotSpawned = current_spawn.state {
+        // The cue goes out on the tick before the wave comes up, like Maiden hitting 70% in game
+        if current_spawn.countdown == Some(1) && config.chat_cue {
+            chat.game(0, MAIDEN_CUE);
+        }
+        if current_spawn.countdown != Some(0) {
+            return;
         }
 
+        if current_spawn.spawns.is_empty() {
+            // Seeded waves are reproducible: the nth wave of a seed is always the same spawn
+            let (spawns, delays) = match config.seed {
+                Some(seed) => generate_wave(&mut wave_rng(seed, current_spawn.wave), &config),
+                None => generate_wave(&mut rand::thread_rng(), &config),
+            };
+            current_spawn.spawns = spawns;
+            current_spawn.delays = delays;
+            current_spawn.wave += 1;
+        }
+
+        current_spawn.pending = current_spawn.spawns.clone();
         current_spawn.state = ActionState::Spawned;
+
+        let spawns = &current_spawn.spawns;
+        chat.game(
+            0,
+            &format!(
+                "Wave spawned: {}",
+                format_delayed_spawns(spawns, &current_spawn.delays)
+            ),
+        );
+        for (teammate, callout) in
+            teammate_callouts(spawns, party_preset(config.players).teammates())
+        {
+            chat.public(0, &teammate, &callout);
+        }
+    }
+
+    // Delayed spawns come up on a later tick than the rest of the wave
+    let tick = current_spawn.tick;
+    let (due, pending): (Vec<SpawnPoint>, Vec<SpawnPoint>) = current_spawn
+        .pending
+        .iter()
+        .copied()
+        .partition(|spawn| current_spawn.delays.get(*spawn) <= tick);
+    current_spawn.pending = pending;
+
+    let hitpoints = config
+        .mode
+        .hitpoints(party_preset(config.players).nylo_hitpoints);
+    for spawn_point in due {
+        spawn_single_nylo(
+            &mut commands,
+            &clickbox,
+            &mut materials,
+            &models,
+            &NyloState::new(spawn_point, hitpoints),
+        );
+    }
+}
+
+// Every wave counts down afresh, so a random countdown can't be learned
+fn start_countdown(
+    mut current_spawn: ResMut<CurrentSpawn>,
+    config: Res<Config>,
+    mut chat: ResMut<Chat>,
+) {
+    chat.start_wave();
+    current_spawn.countdown = match config.countdown {
+        Countdown::Fixed(ticks) => Some(ticks),
+        Countdown::Random(min, max) => Some(rand::thread_rng().gen_range(min..=max.max(min))),
+        Countdown::MaidenHealth => None,
+    };
+
+    // Too short a countdown to have a tick before the wave, so the cue goes out right away
+    if matches!(current_spawn.countdown, Some(0 | 1)) && config.chat_cue {
+        chat.game(0, MAIDEN_CUE);
     }
 }
 
-fn spawn_single_nylo(
+fn generate_wave<R: Rng>(rng: &mut R, config: &Config) -> (Vec<SpawnPoint>, SpawnDelays) {
+    let spawns = generate_spawn_points(rng, party_preset(config.players).spawns);
+    let max_ticks = config.mode.stats().max_scuff_ticks;
+    let delays = scuff_spawns(rng, &spawns, config.scuffed_chance, max_ticks);
+    (spawns, delays)
+}
+
+// Places the Matomenos model relative to its nylo, which sits 0.75 above the floor
+pub fn model_transform() -> Transform {
+    Transform::from_xyz(0.0, -0.75, 0.0)
+        .with_scale(Vec3 {
+            x: 0.0075,
+            y: 0.0075,
+            z: 0.0075,
+        })
+        .with_rotation(Quat::from_rotation_y(std::f32::consts::PI * 1.5))
+}
+
+pub fn spawn_single_nylo(
     commands: &mut Commands,
-    meshes: &mut ResMut<Assets<Mesh>>,
+    clickbox: &Res<Clickbox>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
     models: &Res<ModelAssets>,
-    spawn: SpawnPoint,
+    nylo: &NyloState,
 ) {
     let color_handle = materials.add(Color::NONE.into());
+    let mut color_timer = Timer::from_seconds(4.0 * 0.6, false);
+    color_timer.set_elapsed(nylo.color_elapsed);
 
     commands
-        .spawn_bundle(SpatialBundle::from_transform(Transform::from_xyz(
-            spawn.x, 0.75, spawn.y,
+        .spawn_bundle(SpatialBundle::from_transform(Transform::from_translation(
+            nylo.translation,
         )))
-        .insert(meshes.add(shape::Cube { size: 1.75 }.into()))
-        .insert(color_handle.clone())
-        .insert(NotShadowCaster)
-        .insert(PickRaycastTarget::default())
-        .insert_bundle(PickableBundle::default())
-        .forward_events::<PointerClick, NylocasClicked>()
         .insert(Name::new("Matomenos"))
         .insert(Matomenos {
-            frozen: FrozenState::NotFrozen,
-            color_timer: Timer::from_seconds(4.0 * 0.6, false),
-            color_handle: color_handle,
+            frozen: nylo.frozen,
+            color_timer,
+            color_handle: color_handle.clone(),
+            spawn: nylo.spawn,
+            hitpoints: nylo.hitpoints,
+            max_hitpoints: nylo.max_hitpoints,
+            pending_hit: nylo.pending_hit,
+            previous: nylo.translation,
+            stack_offset: Vec3::ZERO,
+            lag: Vec3::ZERO,
         })
+        .insert(Walker::new(models.matomenos_gltf.clone()))
+        .insert(Hitsplats::new(0.0))
         .with_children(|commands| {
             commands.spawn_bundle(SceneBundle {
                 scene: models.matomenos_model.clone(),
-                transform: Transform::from_xyz(0.0, -0.75, 0.0)
-                    .with_scale(Vec3 {
-                        x: 0.0075,
-                        y: 0.0075,
-                        z: 0.0075,
-                    })
-                    .with_rotation(Quat::from_rotation_y(std::f32::consts::PI * 1.5)),
+                transform: model_transform(),
                 ..Default::default()
             });
+            commands
+                .spawn_bundle(SpatialBundle::default())
+                .insert(clickbox.mesh.clone())
+                .insert(color_handle.clone())
+                .insert(NotShadowCaster)
+                .insert(FreezeOverlay);
         });
 }
 
 fn move_nylos(
     mut commands: Commands,
-    mut query: Query<(Entity, &mut Matomenos, &mut Transform), With<Matomenos>>,
+    mut query: Query<
+        (
+            Entity,
+            &mut Matomenos,
+            &mut Transform,
+            &mut Walker,
+            &mut Hitsplats,
+        ),
+        With<Matomenos>,
+    >,
     mut current_spawn: ResMut<CurrentSpawn>,
+    config: Res<Config>,
+    mut chat: ResMut<Chat>,
 ) {
     match current_spawn.state {
-        ActionState::NotSpawned => return,
+        // Count down to the wave once Spawn has been pressed
+        ActionState::NotSpawned => {
+            if let Some(ticks) = current_spawn.countdown {
+                current_spawn.countdown = Some(ticks.saturating_sub(1));
+            }
+            return;
+        }
         ActionState::Spawned => {
             current_spawn.state = ActionState::Moving;
             return;
@@ -166,76 +457,173 @@ fn move_nylos(
         ActionState::Moving => (),
     }
 
-    for (entity, mut nylo, mut transform) in query.iter_mut() {
-        match nylo.frozen {
-            FrozenState::Frozen => continue,
-            FrozenState::ShouldFreeze => {
-                nylo.frozen = FrozenState::Frozen;
-            }
-            FrozenState::NotFrozen => {}
-        };
+    current_spawn.tick += 1;
 
-        // Distance to Maiden's Southwest Tile
-        let distance_x = 1.5 - transform.translation.x;
-        let distance_z = 13.5 - transform.translation.z;
-
-        // Calculate next tick's X and Z coordinates
-        // - If there is no difference between Maiden's X/Z and the
-        //   nylo's X/Z coordinate, maintain it
-        // - If there is a difference, use the signum function to add
-        //   or subtract 1 unit in that direction
-        let x = if distance_x == 0.0 {
-            transform.translation.x
-        } else {
-            transform.translation.x + distance_x.signum() * 1.0
-        };
+    // Where every nylo stands, kept up to date as they move so they can't step onto each other
+    let mut occupied: Vec<(Entity, (f32, f32))> = query
+        .iter()
+        .map(|(entity, _, transform, _, _)| {
+            (entity, (transform.translation.x, transform.translation.z))
+        })
+        .collect();
 
-        let z = if distance_z == 0.0 {
-            transform.translation.z
-        } else {
-            transform.translation.z + distance_z.signum() * 1.0
-        };
+    for (entity, mut nylo, mut transform, mut walker, mut hitsplats) in query.iter_mut() {
+        nylo.previous = transform.translation;
 
-        // If the nylo will run into Maiden on this tick, despawn it
-        if (x >= 2.0 && x <= 8.0) && (z >= 8.0 && z <= MAP_WIDTH as f32 - 9.0) {
+        if let Some(hit) = nylo.pending_hit.take() {
+            hitsplats.add(hit.damage());
+            match hit {
+                BarrageHit::Hit(damage) => nylo.hitpoints = nylo.hitpoints.saturating_sub(damage),
+                // Any other hit freezes, so only a splash is worth a line
+                BarrageHit::Splash => chat.game(
+                    current_spawn.tick,
+                    &format!("Your Ice Barrage splashed ({})", nylo.spawn.label()),
+                ),
+            }
+        }
+        if nylo.hitpoints == 0 {
             commands.entity(entity).despawn_recursive();
-            current_spawn.leaks += 1;
+            occupied.retain(|(other, _)| *other != entity);
             continue;
         }
 
-        // Update the nylo's position vector with the new X and Z coordinates
-        transform.translation = Vec3::new(x, transform.translation.y, z);
+        let (x, z) = (transform.translation.x, transform.translation.z);
+
+        match step_nylo(&mut nylo.frozen, x, z) {
+            NyloStep::Stay => walker.moving = false,
+            // If the nylo will run into Maiden on this tick, despawn it
+            NyloStep::Leak => {
+                commands.entity(entity).despawn_recursive();
+                current_spawn.leaks += 1;
+                chat.game(
+                    current_spawn.tick,
+                    &format!("A nylocas reached Maiden ({})", nylo.spawn.label()),
+                );
+                occupied.retain(|(other, _)| *other != entity);
+            }
+            // Update the nylo's position vector with the new X and Z coordinates
+            NyloStep::Move(x, z) => {
+                let others = occupied
+                    .iter()
+                    .filter(|(other, _)| *other != entity)
+                    .map(|(_, tile)| *tile);
+                if can_enter(config.stacking, (x, z), others) {
+                    transform.translation = Vec3::new(x, transform.translation.y, z);
+                    walker.moving = true;
+                    for (other, tile) in occupied.iter_mut() {
+                        if *other == entity {
+                            *tile = (x, z);
+                        }
+                    }
+                } else {
+                    walker.moving = false;
+                }
+            }
+        }
+    }
+}
+
+// Nylos sharing a tile, in a fixed order so their models don't swap places
+pub fn stacks<'a>(
+    nylos: impl Iterator<Item = (Entity, &'a Transform)>,
+) -> Vec<((f32, f32), Vec<Entity>)> {
+    let mut stacks: Vec<((f32, f32), Vec<Entity>)> = Vec::new();
+    for (entity, transform) in nylos {
+        let tile = (transform.translation.x, transform.translation.z);
+        match stacks.iter_mut().find(|(other, _)| *other == tile) {
+            Some((_, entities)) => entities.push(entity),
+            None => stacks.push((tile, vec![entity])),
+        }
+    }
+    for (_, entities) in stacks.iter_mut() {
+        entities.sort();
+    }
+    stacks
+}
+
+// The nylo itself is always on its tile, only the model and its freeze overlay are drawn
+// walking there over the tick and spread out from the other nylos on its tile
+fn interpolate_nylos(
+    tick: Res<GameTick>,
+    state: Res<State<GameState>>,
+    mut nylos_query: Query<(Entity, &mut Matomenos, &Transform, &Children)>,
+    mut children_query: Query<(&mut Transform, Option<&FreezeOverlay>), Without<Matomenos>>,
+) {
+    // A paused tick has already finished, so every nylo has arrived
+    let progress = match state.current() {
+        GameState::Paused => 1.0,
+        _ => tick.percent(),
+    };
+
+    let nylos = nylos_query
+        .iter()
+        .map(|(entity, _, transform, _)| (entity, transform));
+    for (_, entities) in stacks(nylos) {
+        for (i, entity) in entities.iter().enumerate() {
+            if let Ok((_, mut nylo, _, _)) = nylos_query.get_mut(*entity) {
+                nylo.stack_offset = if entities.len() > 1 {
+                    let angle = std::f32::consts::TAU * i as f32 / entities.len() as f32;
+                    Vec3::new(angle.cos(), 0.0, angle.sin()) * STACK_SPREAD
+                } else {
+                    Vec3::ZERO
+                };
+            }
+        }
+    }
+
+    for (_, mut nylo, transform, children) in nylos_query.iter_mut() {
+        nylo.lag = (nylo.previous - transform.translation) * (1.0 - progress);
+        for child in children.iter() {
+            if let Ok((mut drawn, overlay)) = children_query.get_mut(*child) {
+                let base = match overlay {
+                    Some(_) => Vec3::ZERO,
+                    None => model_transform().translation,
+                };
+                drawn.translation = base + nylo.lag + nylo.stack_offset;
+            }
+        }
     }
 }
 
 fn draw_freeze(
     time: Res<Time>,
+    tick: Res<GameTick>,
     mut query: Query<&mut Matomenos>,
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
     for mut nylo in query.iter_mut() {
         match nylo.frozen {
-            FrozenState::Frozen => {
-                nylo.color_timer.tick(time.delta());
+            FrozenState::Frozen(_) => {
+                nylo.color_timer.tick(tick.scale(time.delta()));
                 if nylo.color_timer.just_finished() {
-                    let mut color_mat = materials.get_mut(&nylo.color_handle).unwrap();
+                    let color_mat = materials.get_mut(&nylo.color_handle).unwrap();
                     color_mat.base_color = Color::NONE;
                 }
                 if nylo.color_timer.finished() {
                     continue;
                 }
-                let mut color_mat = materials.get_mut(&nylo.color_handle).unwrap();
+                let color_mat = materials.get_mut(&nylo.color_handle).unwrap();
                 color_mat.base_color = Color::rgba(1.0, 1.0, 1.0, nylo.color_timer.percent_left());
             }
             FrozenState::ShouldFreeze => {
-                let mut color_mat = materials.get_mut(&nylo.color_handle).unwrap();
+                let color_mat = materials.get_mut(&nylo.color_handle).unwrap();
                 color_mat.base_color = Color::rgba(1.0, 1.0, 1.0, nylo.color_timer.percent_left());
             }
-            FrozenState::NotFrozen => {}
+            FrozenState::NotFrozen | FrozenState::Immune(_) => {}
         };
     }
 }
 
+fn start_queued_spawn(
+    mut current_spawn: ResMut<CurrentSpawn>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if current_spawn.queued {
+        current_spawn.queued = false;
+        state.push(GameState::Spawned).unwrap();
+    }
+}
+
 fn reset(
     mut commands: Commands,
     mut matomenos: Query<Entity, With<Matomenos>>,
@@ -246,55 +634,86 @@ fn reset(
     }
 
     current_spawn.state = ActionState::NotSpawned;
-    current_spawn.spawn_delay.reset();
+    current_spawn.countdown = None;
+    current_spawn.pending.clear();
 
     if !current_spawn.rerun {
         current_spawn.spawns.clear();
+        current_spawn.delays = SpawnDelays::default();
     }
 
+    current_spawn.tick = 0;
+    current_spawn.casts.clear();
     current_spawn.leaks = 0;
+    current_spawn.multi_freezes = MultiFreezes::default();
     current_spawn.rerun = false;
 }
 
-struct NylocasClicked(Entity);
-impl ForwardedEvent<PointerClick> for NylocasClicked {
-    fn from_data(event_data: &PointerEventData<PointerClick>) -> NylocasClicked {
-        NylocasClicked(event_data.listener())
-    }
+// Sent when the player picks Cast on a nylo, from the mouse or a replay
+pub struct NylocasClicked(pub Entity);
+
+// Sent whenever an Ice Barrage goes out, with every nylo it caught
+pub struct BarrageCast {
+    pub caught: Vec<Entity>,
 }
+
 impl NylocasClicked {
     fn handle_events(
         mut events: EventReader<NylocasClicked>,
-        mut nylos_query: Query<(&Transform, &mut Matomenos), With<Matomenos>>,
+        mut nylos_query: Query<(Entity, &Transform, &mut Matomenos), With<Matomenos>>,
         mut spell_query: Query<&mut Spell>,
         mut player_query: Query<&mut Player, With<Player>>,
+        mut current_spawn: ResMut<CurrentSpawn>,
+        mut casts: EventWriter<BarrageCast>,
     ) {
         let mut spell = spell_query.single_mut();
         let mut player = player_query.single_mut();
         if player.attack_delay != 0 || !spell.is_active {
             return;
         }
+        let mut rng = rand::thread_rng();
 
         for event in events.iter() {
-            let (target_x, target_z) = match nylos_query.get(event.0) {
-                Ok((t, _)) => (t.translation.x, t.translation.z),
+            let (target_x, target_z, target_spawn) = match nylos_query.get(event.0) {
+                Ok((_, t, nylo)) => (t.translation.x, t.translation.z, nylo.spawn),
                 Err(_) => continue,
             };
 
             // For every nylo on the map, search in a 3x3 area for positions next to the target
-            for (transform, mut matomenos) in nylos_query.iter_mut() {
+            let mut cast = false;
+            let mut caught = vec![];
+            let mut caught_tiles = vec![];
+            for (entity, transform, mut matomenos) in nylos_query.iter_mut() {
                 match matomenos.frozen {
                     FrozenState::NotFrozen => (),
                     _ => continue,
                 }
 
                 let (x, z) = (transform.translation.x, transform.translation.z);
-                if f32::abs(target_x - x) <= 1.0 && f32::abs(target_z - z) <= 1.0 {
-                    matomenos.frozen = FrozenState::ShouldFreeze;
-                    player.attack_delay = 5;
-                    spell.is_active = false;
+                if in_barrage_area((target_x, target_z), (x, z)) {
+                    let hit = BarrageHit::roll(&mut rng);
+                    if let BarrageHit::Hit(_) = hit {
+                        matomenos.frozen = FrozenState::ShouldFreeze;
+                        caught.push(entity);
+                        caught_tiles.push((x, z));
+                    }
+                    matomenos.pending_hit = Some(hit);
+                    cast = true;
                 }
             }
+
+            if cast {
+                player.attack_delay = CAST_DELAY;
+                spell.is_active = false;
+                current_spawn.multi_freezes.credit(&caught_tiles);
+                let tick = current_spawn.tick;
+                current_spawn.casts.push(ReplayCast {
+                    tick,
+                    target: target_spawn,
+                });
+                casts.send(BarrageCast { caught });
+                break;
+            }
         }
     }
 }
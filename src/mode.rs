@@ -0,0 +1,131 @@
+// Theatre of Blood difficulties, which change the numbers but not the room
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Mode {
+    Entry,
+    #[default]
+    Normal,
+    Hard,
+}
+
+pub const MODES: &[(&str, Mode)] = &[
+    ("entry", Mode::Entry),
+    ("normal", Mode::Normal),
+    ("hard", Mode::Hard),
+];
+
+// Everything a difficulty changes. Hitpoints are given as a share of the normal mode ones.
+// The barrage is the player's spell and the same in every mode, so it stays in sim.rs.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ModeStats {
+    pub hitpoints_percent: u32,
+    // The player's melee hit on a blood spawn
+    pub blood_spawn_max_hit: u32,
+    // Ticks between Maiden's blood throws, how hard the blood hits and how long the pools last
+    pub attack_ticks: u32,
+    pub magic_max_hit: u32,
+    pub pool_max_hit: u32,
+    pub blood_pool_ticks: u32,
+    pub blood_spawn_heal: u32,
+    // Maiden's health above the threshold when the health countdown starts, and lost
+    // each tick until the nylos come up, in percent
+    pub health_headroom: (f32, f32),
+    pub health_drain: (f32, f32),
+    // Defaults for the spawn timing, which the Config window can still change. These
+    // are trainer settings rather than game numbers: entry gives more warning and
+    // clean spawns, hard less warning and more scuffed ones.
+    pub countdown: u32,
+    pub scuffed_chance: f64,
+    pub max_scuff_ticks: u32,
+}
+
+const ENTRY_STATS: ModeStats = ModeStats {
+    hitpoints_percent: 50,
+    blood_spawn_max_hit: 40,
+    attack_ticks: 14,
+    magic_max_hit: 20,
+    pool_max_hit: 5,
+    blood_pool_ticks: 16,
+    blood_spawn_heal: 2,
+    health_headroom: (4.0, 12.0),
+    health_drain: (0.1, 0.8),
+    countdown: 8,
+    scuffed_chance: 0.0,
+    max_scuff_ticks: 1,
+};
+
+const NORMAL_STATS: ModeStats = ModeStats {
+    hitpoints_percent: 100,
+    blood_spawn_max_hit: 40,
+    attack_ticks: 10,
+    magic_max_hit: 36,
+    pool_max_hit: 10,
+    blood_pool_ticks: 16,
+    blood_spawn_heal: 5,
+    health_headroom: (4.0, 12.0),
+    health_drain: (0.2, 1.2),
+    countdown: 5,
+    scuffed_chance: 0.0,
+    max_scuff_ticks: 2,
+};
+
+const HARD_STATS: ModeStats = ModeStats {
+    hitpoints_percent: 125,
+    blood_spawn_max_hit: 40,
+    attack_ticks: 8,
+    magic_max_hit: 46,
+    pool_max_hit: 14,
+    blood_pool_ticks: 16,
+    blood_spawn_heal: 8,
+    health_headroom: (4.0, 12.0),
+    health_drain: (0.4, 1.6),
+    countdown: 3,
+    scuffed_chance: 0.2,
+    max_scuff_ticks: 2,
+};
+
+impl Mode {
+    pub fn from_name(name: &str) -> Option<Mode> {
+        MODES
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, mode)| *mode)
+    }
+
+    // Position in MODES, which share codes store, so the order can't change
+    pub fn index(&self) -> usize {
+        MODES.iter().position(|(_, mode)| mode == self).unwrap()
+    }
+
+    pub fn from_index(index: usize) -> Option<Mode> {
+        MODES.get(index).map(|(_, mode)| *mode)
+    }
+
+    pub fn name(&self) -> &'static str {
+        MODES
+            .iter()
+            .find(|(_, mode)| mode == self)
+            .map(|(name, _)| *name)
+            .unwrap()
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Mode::Entry => "Entry Mode",
+            Mode::Normal => "Normal",
+            Mode::Hard => "Hard Mode",
+        }
+    }
+
+    pub fn stats(&self) -> &'static ModeStats {
+        match self {
+            Mode::Entry => &ENTRY_STATS,
+            Mode::Normal => &NORMAL_STATS,
+            Mode::Hard => &HARD_STATS,
+        }
+    }
+
+    // Scales normal mode hitpoints to this difficulty
+    pub fn hitpoints(&self, hitpoints: u32) -> u32 {
+        std::cmp::max(1, hitpoints * self.stats().hitpoints_percent / 100)
+    }
+}
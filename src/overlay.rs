@@ -0,0 +1,299 @@
+use crate::camera;
+use crate::clickbox::{convex_outline, Clickbox, ConvexHull};
+use crate::cursor::{tile_center, Hovered};
+use crate::matomenos::Matomenos;
+use crate::player::Player;
+use crate::sim::{in_barrage_area, CAST_RANGE};
+use crate::spawn_point::SpawnPoint;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_egui::egui::collapsing_header::CollapsingState;
+use bevy_egui::{egui, EguiContext};
+
+pub struct OverlayPlugin;
+
+// Floor area covered by the tile grid
+const GRID_X: (f32, f32) = (0.0, 28.0);
+const GRID_Z: (f32, f32) = (0.0, 23.0);
+
+// One outline colour per spawn point, in SPAWN_POINTS order
+const SPAWN_COLORS: &[(u8, u8, u8)] = &[
+    (255, 80, 80),
+    (255, 160, 60),
+    (255, 230, 70),
+    (160, 230, 70),
+    (70, 200, 120),
+    (70, 210, 230),
+    (80, 140, 255),
+    (150, 100, 255),
+    (230, 90, 230),
+    (255, 130, 180),
+];
+
+// RuneLite-style overlays, each of which can be toggled on its own
+pub struct OverlaySettings {
+    pub tile_grid: bool,
+    pub true_tiles: bool,
+    pub barrage_preview: bool,
+    pub cast_range: bool,
+    pub spawn_colors: bool,
+    pub hover_highlight: bool,
+    pub highlight_neighbours: bool,
+    pub highlight_color: egui::Color32,
+    pub health_bars: bool,
+    pub freeze_timers: bool,
+    pub spawn_labels: bool,
+    pub hitsplats: bool,
+    pub stack_badges: bool,
+}
+
+impl Default for OverlaySettings {
+    fn default() -> OverlaySettings {
+        OverlaySettings {
+            tile_grid: false,
+            true_tiles: true,
+            barrage_preview: true,
+            cast_range: false,
+            spawn_colors: true,
+            hover_highlight: true,
+            highlight_neighbours: true,
+            highlight_color: egui::Color32::from_rgb(0, 255, 255),
+            health_bars: true,
+            freeze_timers: true,
+            spawn_labels: false,
+            hitsplats: true,
+            stack_badges: true,
+        }
+    }
+}
+
+impl Plugin for OverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OverlaySettings>()
+            .add_system(overlay_ui)
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(draw_overlays))
+            .add_system_set(SystemSet::on_update(GameState::Spawned).with_system(draw_overlays))
+            .add_system_set(SystemSet::on_update(GameState::Paused).with_system(draw_overlays));
+    }
+}
+
+pub fn spawn_color(spawn: SpawnPoint) -> egui::Color32 {
+    let (r, g, b) = spawn.index().map_or((255, 255, 255), |i| SPAWN_COLORS[i]);
+    egui::Color32::from_rgb(r, g, b)
+}
+
+// Projects world positions onto the screen so overlays can be painted with egui
+pub struct Projector<'a> {
+    camera: &'a Camera,
+    transform: &'a GlobalTransform,
+    height: f32,
+}
+
+impl<'a> Projector<'a> {
+    pub fn new(camera: &'a Camera, transform: &'a GlobalTransform) -> Option<Projector<'a>> {
+        let height = camera.logical_viewport_size()?.y;
+        Some(Projector {
+            camera,
+            transform,
+            height,
+        })
+    }
+
+    pub fn project(&self, world: Vec3) -> Option<egui::Pos2> {
+        // Viewport coordinates start at the bottom left, egui's at the top left
+        let viewport = self.camera.world_to_viewport(self.transform, world)?;
+        Some(egui::pos2(viewport.x, self.height - viewport.y))
+    }
+
+    // Outline of a square on the floor centred on (x, z), e.g. 0.5 for a single tile
+    pub fn floor_square(&self, x: f32, z: f32, half_size: f32) -> Option<Vec<egui::Pos2>> {
+        [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .iter()
+            .map(|(dx, dz)| self.project(Vec3::new(x + dx * half_size, 0.0, z + dz * half_size)))
+            .collect()
+    }
+}
+
+// A window that starts folded up to its title bar. This egui's windows have no
+// default_open, so the folded state is stored before the window first shows.
+pub fn folded_window(ctx: &egui::Context, title: &str) -> egui::Window<'static> {
+    let id = egui::Id::new(title).with("collapsing");
+    if CollapsingState::load(ctx, id).is_none() {
+        CollapsingState::load_with_default_open(ctx, id, false).store(ctx);
+    }
+    egui::Window::new(title)
+}
+
+// Paints behind every egui window, so overlays never cover the UI
+pub fn painter(ctx: &egui::Context, name: &str) -> egui::Painter {
+    ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Background,
+        egui::Id::new(name),
+    ))
+}
+
+fn overlay_ui(mut egui_context: ResMut<EguiContext>, mut settings: ResMut<OverlaySettings>) {
+    folded_window(egui_context.ctx_mut(), "Overlays").show(egui_context.ctx_mut(), |ui| {
+        ui.checkbox(&mut settings.tile_grid, "Tile grid");
+        ui.checkbox(&mut settings.true_tiles, "True tiles");
+        ui.checkbox(&mut settings.barrage_preview, "3x3 barrage preview");
+        ui.checkbox(&mut settings.cast_range, "Cast range");
+        ui.checkbox(&mut settings.spawn_colors, "Colour by spawn");
+        ui.separator();
+        ui.checkbox(&mut settings.hover_highlight, "Highlight hovered nylo");
+        ui.checkbox(
+            &mut settings.highlight_neighbours,
+            "Highlight its 3x3 neighbours",
+        );
+        ui.horizontal(|ui| {
+            ui.color_edit_button_srgba(&mut settings.highlight_color);
+            ui.label("Highlight colour");
+        });
+        ui.separator();
+        ui.checkbox(&mut settings.health_bars, "Health bars");
+        ui.checkbox(&mut settings.freeze_timers, "Freeze and immunity timers");
+        ui.checkbox(&mut settings.spawn_labels, "Spawn labels");
+        ui.checkbox(&mut settings.hitsplats, "Hitsplats");
+        ui.checkbox(&mut settings.stack_badges, "Stack counts");
+    });
+}
+
+fn draw_overlays(
+    mut egui_context: ResMut<EguiContext>,
+    settings: Res<OverlaySettings>,
+    hovered: Res<Hovered>,
+    clickbox: Res<Clickbox>,
+    nylos_query: Query<(Entity, &Transform, &Matomenos)>,
+    player_query: Query<&Transform, With<Player>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<camera::Camera>>,
+) {
+    let projector = match camera_query
+        .get_single()
+        .ok()
+        .and_then(|(camera, transform)| Projector::new(camera, transform))
+    {
+        Some(projector) => projector,
+        None => return,
+    };
+    let painter = painter(egui_context.ctx_mut(), "overlays");
+
+    if settings.tile_grid {
+        let stroke = egui::Stroke::new(1.0, egui::Color32::from_white_alpha(40));
+        let line = |from: Vec3, to: Vec3| {
+            if let (Some(from), Some(to)) = (projector.project(from), projector.project(to)) {
+                painter.line_segment([from, to], stroke);
+            }
+        };
+        for x in GRID_X.0 as i32..=GRID_X.1 as i32 {
+            let x = x as f32;
+            line(Vec3::new(x, 0.0, GRID_Z.0), Vec3::new(x, 0.0, GRID_Z.1));
+        }
+        for z in GRID_Z.0 as i32..=GRID_Z.1 as i32 {
+            let z = z as f32;
+            line(Vec3::new(GRID_X.0, 0.0, z), Vec3::new(GRID_X.1, 0.0, z));
+        }
+    }
+
+    let player_tile = player_query
+        .get_single()
+        .ok()
+        .map(|transform| tile_center(transform.translation));
+
+    if let Some((x, z)) = player_tile {
+        if settings.true_tiles {
+            if let Some(square) = projector.floor_square(x, z, 0.5) {
+                painter.add(egui::Shape::closed_line(
+                    square,
+                    egui::Stroke::new(1.5, egui::Color32::from_rgb(0, 255, 255)),
+                ));
+            }
+        }
+        if settings.cast_range {
+            if let Some(square) = projector.floor_square(x, z, CAST_RANGE + 0.5) {
+                painter.add(egui::Shape::closed_line(
+                    square,
+                    egui::Stroke::new(
+                        1.5,
+                        egui::Color32::from_rgba_unmultiplied(120, 200, 255, 160),
+                    ),
+                ));
+            }
+        }
+    }
+
+    let hovered_tile = hovered
+        .nylo
+        .and_then(|entity| nylos_query.get(entity).ok())
+        .map(|(_, transform, _)| (transform.translation.x, transform.translation.z));
+
+    for (_, transform, nylo) in nylos_query.iter() {
+        let (x, z) = (transform.translation.x, transform.translation.z);
+        let caught = settings.barrage_preview
+            && hovered_tile.is_some_and(|target| in_barrage_area(target, (x, z)));
+
+        if settings.true_tiles || caught {
+            let color = if caught {
+                egui::Color32::WHITE
+            } else if settings.spawn_colors {
+                spawn_color(nylo.spawn())
+            } else {
+                egui::Color32::from_rgb(255, 255, 0)
+            };
+            if let Some(square) = projector.floor_square(x, z, 0.5) {
+                painter.add(egui::Shape::closed_line(
+                    square,
+                    egui::Stroke::new(1.5, color),
+                ));
+            }
+        }
+    }
+
+    if let (true, Some((x, z))) = (settings.barrage_preview, hovered_tile) {
+        if let Some(square) = projector.floor_square(x, z, 1.5) {
+            painter.add(egui::Shape::convex_polygon(
+                square,
+                egui::Color32::from_rgba_unmultiplied(120, 200, 255, 40),
+                egui::Stroke::new(2.0, egui::Color32::from_rgb(120, 200, 255)),
+            ));
+        }
+    }
+
+    if let (true, Some(target)) = (settings.hover_highlight, hovered.nylo) {
+        for (entity, transform, nylo) in nylos_query.iter() {
+            let position = (transform.translation.x, transform.translation.z);
+            let neighbour = settings.highlight_neighbours
+                && hovered_tile.is_some_and(|tile| in_barrage_area(tile, position));
+            if entity == target || neighbour {
+                let width = if entity == target { 2.0 } else { 1.0 };
+                draw_outline(
+                    &painter,
+                    &projector,
+                    &clickbox.hull,
+                    transform.translation + nylo.stack_offset(),
+                    egui::Stroke::new(width, settings.highlight_color),
+                );
+            }
+        }
+    }
+}
+
+// Screen-space outline around a nylo's clickbox, like RuneLite's NPC highlight
+fn draw_outline(
+    painter: &egui::Painter,
+    projector: &Projector,
+    hull: &ConvexHull,
+    position: Vec3,
+    stroke: egui::Stroke,
+) {
+    let points = hull
+        .corners()
+        .into_iter()
+        .filter_map(|corner| projector.project(position + corner))
+        .map(|point| Vec2::new(point.x, point.y))
+        .collect();
+    let outline = convex_outline(points)
+        .into_iter()
+        .map(|point| egui::pos2(point.x, point.y))
+        .collect();
+    painter.add(egui::Shape::closed_line(outline, stroke));
+}
@@ -0,0 +1,66 @@
+use crate::sim::NYLO_HITPOINTS;
+use crate::spawn_point::MAX_SPAWNS;
+
+// Maiden's hitpoints in a full team, which smaller teams get a share of
+const MAIDEN_HITPOINTS: u32 = 3500;
+
+// Everything that scales with the size of the team in the room
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PartyPreset {
+    pub name: &'static str,
+    pub players: usize,
+    pub maiden_hitpoints: u32,
+    pub nylo_hitpoints: u32,
+    pub spawns: usize,
+}
+
+impl PartyPreset {
+    // Everyone in the team but the player
+    pub fn teammates(&self) -> usize {
+        self.players - 1
+    }
+}
+
+// Teams of three or fewer share the trio's scaling, and a full team uses every spawn point
+pub const PARTY_PRESETS: &[PartyPreset] = &[
+    PartyPreset {
+        name: "Solo",
+        players: 1,
+        maiden_hitpoints: MAIDEN_HITPOINTS * 3 / 4,
+        nylo_hitpoints: NYLO_HITPOINTS * 3 / 4,
+        spawns: 2,
+    },
+    PartyPreset {
+        name: "Duo",
+        players: 2,
+        maiden_hitpoints: MAIDEN_HITPOINTS * 3 / 4,
+        nylo_hitpoints: NYLO_HITPOINTS * 3 / 4,
+        spawns: 4,
+    },
+    PartyPreset {
+        name: "Trio",
+        players: 3,
+        maiden_hitpoints: MAIDEN_HITPOINTS * 3 / 4,
+        nylo_hitpoints: NYLO_HITPOINTS * 3 / 4,
+        spawns: 6,
+    },
+    PartyPreset {
+        name: "4-man",
+        players: 4,
+        maiden_hitpoints: MAIDEN_HITPOINTS * 7 / 8,
+        nylo_hitpoints: NYLO_HITPOINTS * 7 / 8,
+        spawns: 8,
+    },
+    PartyPreset {
+        name: "5-man",
+        players: 5,
+        maiden_hitpoints: MAIDEN_HITPOINTS,
+        nylo_hitpoints: NYLO_HITPOINTS,
+        spawns: MAX_SPAWNS,
+    },
+];
+
+// The preset for a party size, clamped to the sizes the room allows
+pub fn party_preset(players: usize) -> &'static PartyPreset {
+    &PARTY_PRESETS[players.clamp(1, PARTY_PRESETS.len()) - 1]
+}
@@ -1,26 +1,99 @@
 use crate::actions::Actions;
+use crate::animation::Walker;
+use crate::billboard::Hitsplats;
+use crate::chat::Chat;
+use crate::inventory::{starting_inventory, Item, Protection, EAT_DELAY, SHARK_HEAL};
 use crate::loading::ModelAssets;
+use crate::matomenos::CurrentSpawn;
+use crate::tick::on_game_tick;
 use crate::GameState;
 use bevy::prelude::*;
-use bevy::time::FixedTimestep;
 
 pub struct PlayerPlugin;
 
-#[derive(Component)]
+pub const PLAYER_HITPOINTS: u32 = 99;
+pub const PLAYER_PRAYER: u32 = 99;
+
+#[derive(Component, Clone)]
 pub struct Player {
     pub attack_delay: i32,
+    pub hitpoints: u32,
+    pub prayer: u32,
+    pub protection: Option<Protection>,
+    pub inventory: Vec<Option<Item>>,
+    // Ticks until the player can eat again
+    pub eat_delay: i32,
+    // Drain built up towards the next prayer point
+    pub prayer_drain: u32,
+}
+
+impl Default for Player {
+    fn default() -> Player {
+        Player {
+            attack_delay: 0,
+            hitpoints: PLAYER_HITPOINTS,
+            prayer: PLAYER_PRAYER,
+            protection: None,
+            inventory: starting_inventory(),
+            eat_delay: 0,
+            prayer_drain: 0,
+        }
+    }
+}
+
+impl Player {
+    // Deals a hit, which the matching protection prayer blocks completely
+    pub fn hit(&mut self, damage: u32, style: Option<Protection>) -> u32 {
+        let damage = match (style, self.protection) {
+            (Some(style), Some(protection)) if style == protection => 0,
+            _ => damage,
+        };
+        self.hitpoints = self.hitpoints.saturating_sub(damage);
+        damage
+    }
+
+    // Eats or drinks the item in a slot, if the player isn't still eating the last one
+    pub fn consume(&mut self, slot: usize) {
+        if self.eat_delay > 0 {
+            return;
+        }
+        let item = match self.inventory.get(slot).copied().flatten() {
+            Some(item) => item,
+            None => return,
+        };
+
+        self.hitpoints = match item {
+            Item::Shark => std::cmp::max(
+                self.hitpoints,
+                std::cmp::min(self.hitpoints + SHARK_HEAL, PLAYER_HITPOINTS),
+            ),
+            // Brews heal past the maximum, by 15% plus two
+            Item::SaradominBrew(_) => {
+                let heal = PLAYER_HITPOINTS * 15 / 100 + 2;
+                std::cmp::max(
+                    self.hitpoints,
+                    std::cmp::min(self.hitpoints + heal, PLAYER_HITPOINTS + heal),
+                )
+            }
+        };
+        self.inventory[slot] = item.consumed();
+        self.attack_delay += EAT_DELAY;
+        self.eat_delay = EAT_DELAY;
+    }
 }
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.add_system_set(SystemSet::on_enter(GameState::Playing).with_system(spawn_player))
             .add_system_set(SystemSet::on_update(GameState::Playing).with_system(move_player))
-            .add_system_set(SystemSet::on_update(GameState::Spawned).with_system(move_player))
             .add_system_set(
-                SystemSet::new()
-                    .with_run_criteria(FixedTimestep::step(0.6))
-                    .with_system(attack_delay),
-            );
+                SystemSet::on_update(GameState::Spawned)
+                    .with_system(move_player)
+                    .with_system(die),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Paused).with_system(die))
+            .add_system_set(delay_systems())
+            .add_system_set(SystemSet::on_exit(GameState::Spawned).with_system(reset_player));
     }
 }
 
@@ -38,32 +111,109 @@ fn spawn_player(mut commands: Commands, models: Res<ModelAssets>) {
             ..Default::default()
         })
         .insert(Name::new("Player"))
-        .insert(Player { attack_delay: 0 });
+        .insert(Player::default())
+        .insert(Hitsplats::new(1.0))
+        .insert(Walker::new(models.player_gltf.clone()));
 }
 
 fn move_player(
     time: Res<Time>,
-    actions: Res<Actions>,
-    mut player_query: Query<&mut Transform, With<Player>>,
+    mut actions: ResMut<Actions>,
+    mut player_query: Query<(&mut Transform, &mut Walker), With<Player>>,
 ) {
-    if actions.player_movement.is_none() {
-        return;
-    }
-
     let speed = 10.0;
-    let movement = Vec3::new(
-        actions.player_movement.unwrap().x * speed * time.delta_seconds(),
-        0.0,
-        actions.player_movement.unwrap().y * speed * time.delta_seconds(),
-    );
-
-    for mut player_transform in &mut player_query {
-        player_transform.translation += movement;
+    let step = speed * time.delta_seconds();
+
+    for (mut player_transform, mut walker) in &mut player_query {
+        walker.moving = actions.is_moving();
+        if let Some(direction) = actions.player_movement {
+            player_transform.translation += Vec3::new(direction.x, 0.0, direction.y) * step;
+        } else if let Some(target) = actions.walk_target {
+            let target = Vec3::new(target.x, player_transform.translation.y, target.y);
+            let remaining = target - player_transform.translation;
+            if remaining.length() <= step {
+                player_transform.translation = target;
+                actions.walk_target = None;
+            } else {
+                player_transform.translation += remaining.normalize() * step;
+            }
+        }
     }
 }
 
+// Delays count down in the room too, since the player can eat there before a wave
+fn delay_systems() -> SystemSet {
+    SystemSet::new()
+        .with_run_criteria(on_game_tick)
+        .with_system(attack_delay)
+}
+
 fn attack_delay(mut player_query: Query<&mut Player, With<Player>>) {
     for mut player in &mut player_query {
         player.attack_delay = std::cmp::max(0, player.attack_delay - 1);
+        player.eat_delay = std::cmp::max(0, player.eat_delay - 1);
+    }
+}
+
+// Dying ends the wave, back in the room like after Reset. A paused wave is left
+// in two steps, since only one state change goes through per frame.
+fn die(
+    player_query: Query<&Player>,
+    current_spawn: Res<CurrentSpawn>,
+    mut chat: ResMut<Chat>,
+    mut state: ResMut<State<GameState>>,
+) {
+    let dead = player_query
+        .get_single()
+        .is_ok_and(|player| player.hitpoints == 0);
+    if !dead || state.pop().is_err() {
+        return;
+    }
+    if *state.current() == GameState::Spawned {
+        chat.game(current_spawn.tick(), "Oh dear, you are dead!");
+    }
+}
+
+// Every attempt at a wave starts at full health
+fn reset_player(mut player_query: Query<&mut Player>) {
+    for mut player in player_query.iter_mut() {
+        *player = Player::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick::GameTick;
+    use bevy::ecs::schedule::Stage;
+
+    #[test]
+    fn eating_before_a_wave_wears_off_in_the_room() {
+        let mut world = World::new();
+        world.insert_resource(GameTick::ticked());
+        let player = world
+            .spawn()
+            .insert(Player {
+                hitpoints: 50,
+                ..Default::default()
+            })
+            .id();
+
+        // The first shark, after the brews
+        world.get_mut::<Player>(player).unwrap().consume(4);
+        let eaten = world.get::<Player>(player).unwrap();
+        assert_eq!(eaten.hitpoints, 50 + SHARK_HEAL);
+        assert_eq!(
+            (eaten.attack_delay, eaten.eat_delay),
+            (EAT_DELAY, EAT_DELAY)
+        );
+
+        // No wave is up, but the tick still runs in the room
+        let mut stage = SystemStage::single_threaded().with_system_set(delay_systems());
+        for _ in 0..EAT_DELAY {
+            stage.run(&mut world);
+        }
+        let rested = world.get::<Player>(player).unwrap();
+        assert_eq!((rested.attack_delay, rested.eat_delay), (0, 0));
     }
 }
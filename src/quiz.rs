@@ -0,0 +1,357 @@
+use crate::camera::{self, CAMERA_EYE, CAMERA_TARGET};
+use crate::loading::ModelAssets;
+use crate::matomenos::model_transform;
+use crate::spawn_point::{
+    format_spawns, generate_spawn_points, parse_spawns, SpawnPoint, SPAWN_POINTS,
+};
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use smooth_bevy_cameras::LookTransform;
+
+pub struct QuizPlugin;
+
+// Spawns shown and the seconds they stay up at each level, getting harder as the trainee improves
+const LEVELS: &[(usize, f32)] = &[(2, 2.4), (3, 2.0), (4, 1.6), (5, 1.2), (6, 0.8)];
+
+// Correct answers in a row before moving up a level. A wrong answer moves down one.
+const LEVEL_UP_STREAK: u32 = 2;
+
+const SPAWN_COLUMNS: usize = 5;
+
+// A nylo standing on a spawn while the pattern is shown
+#[derive(Component)]
+struct QuizNylo;
+
+enum QuizPhase {
+    // The spawn is up until the timer runs out
+    Showing(Timer),
+    // The spawn is hidden, since this many seconds after startup
+    Answering(f64),
+    Answered { correct: bool, seconds: f64 },
+}
+
+struct Quiz {
+    // Index into LEVELS
+    level: usize,
+    streak: u32,
+    spawns: Vec<SpawnPoint>,
+    phase: QuizPhase,
+    // The answer so far, clicked or typed as chat notation
+    picked: Vec<SpawnPoint>,
+    typed: String,
+    error: Option<String>,
+    // Set when the spawn is hidden, so the answer box takes focus once and clicks can move it
+    focus_answer: bool,
+    rounds: u32,
+    correct: u32,
+    total_seconds: f64,
+}
+
+impl Default for Quiz {
+    fn default() -> Quiz {
+        Quiz {
+            level: 0,
+            streak: 0,
+            spawns: Vec::new(),
+            phase: QuizPhase::Answering(0.0),
+            picked: Vec::new(),
+            typed: String::new(),
+            error: None,
+            focus_answer: false,
+            rounds: 0,
+            correct: 0,
+            total_seconds: 0.0,
+        }
+    }
+}
+
+impl Quiz {
+    fn accuracy(&self) -> f64 {
+        if self.rounds == 0 {
+            return 0.0;
+        }
+        100.0 * self.correct as f64 / self.rounds as f64
+    }
+
+    fn average_seconds(&self) -> f64 {
+        if self.rounds == 0 {
+            return 0.0;
+        }
+        self.total_seconds / self.rounds as f64
+    }
+
+    // Typed notation wins over clicked spawns, so either can be used
+    fn answer(&self) -> Result<Vec<SpawnPoint>, String> {
+        if self.typed.trim().is_empty() {
+            let picked = SPAWN_POINTS
+                .iter()
+                .filter(|spawn| self.picked.contains(spawn))
+                .copied()
+                .collect();
+            return Ok(picked);
+        }
+        parse_spawns(&self.typed).map(|(spawns, _)| spawns)
+    }
+
+    fn submit(&mut self, answer: Vec<SpawnPoint>, now: f64) {
+        let since = match self.phase {
+            QuizPhase::Answering(since) => since,
+            _ => return,
+        };
+        let seconds = now - since;
+        let correct = answer == self.spawns;
+
+        self.rounds += 1;
+        self.total_seconds += seconds;
+        if correct {
+            self.correct += 1;
+            self.streak += 1;
+            if self.streak >= LEVEL_UP_STREAK && self.level + 1 < LEVELS.len() {
+                self.level += 1;
+                self.streak = 0;
+            }
+        } else {
+            self.level = self.level.saturating_sub(1);
+            self.streak = 0;
+        }
+        self.phase = QuizPhase::Answered { correct, seconds };
+    }
+}
+
+impl Plugin for QuizPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Quiz>()
+            .add_system_set(SystemSet::on_enter(GameState::Quiz).with_system(start_quiz))
+            .add_system_set(
+                SystemSet::on_update(GameState::Quiz)
+                    .with_system(hide_spawn)
+                    .with_system(quiz_ui),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Quiz).with_system(despawn_quiz_nylos));
+    }
+}
+
+// Puts a new pattern on the floor for the current level
+fn start_round(commands: &mut Commands, models: &ModelAssets, quiz: &mut Quiz) {
+    let (count, seconds) = LEVELS[quiz.level];
+    quiz.spawns = generate_spawn_points(&mut rand::thread_rng(), count);
+    quiz.phase = QuizPhase::Showing(Timer::from_seconds(seconds, false));
+    quiz.picked.clear();
+    quiz.typed.clear();
+    quiz.error = None;
+
+    for spawn in &quiz.spawns {
+        commands
+            .spawn_bundle(SpatialBundle::from_transform(Transform::from_xyz(
+                spawn.x, 0.75, spawn.y,
+            )))
+            .insert(Name::new("Quiz nylo"))
+            .insert(QuizNylo)
+            .with_children(|commands| {
+                commands.spawn_bundle(SceneBundle {
+                    scene: models.matomenos_model.clone(),
+                    transform: model_transform(),
+                    ..Default::default()
+                });
+            });
+    }
+}
+
+// Every quiz starts from the easiest level, seen from where the camera starts in the room
+fn start_quiz(
+    mut commands: Commands,
+    models: Res<ModelAssets>,
+    mut quiz: ResMut<Quiz>,
+    mut camera_query: Query<&mut LookTransform, With<camera::Camera>>,
+) {
+    for mut look in camera_query.iter_mut() {
+        look.eye = CAMERA_EYE;
+        look.target = CAMERA_TARGET;
+    }
+
+    *quiz = Quiz::default();
+    start_round(&mut commands, &models, &mut quiz);
+}
+
+fn hide_spawn(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut quiz: ResMut<Quiz>,
+    query: Query<Entity, With<QuizNylo>>,
+) {
+    let finished = match &mut quiz.phase {
+        QuizPhase::Showing(timer) => timer.tick(time.delta()).finished(),
+        _ => false,
+    };
+    if finished {
+        for entity in query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        quiz.phase = QuizPhase::Answering(time.seconds_since_startup());
+        quiz.focus_answer = true;
+    }
+}
+
+fn quiz_ui(
+    mut commands: Commands,
+    mut egui_context: ResMut<EguiContext>,
+    time: Res<Time>,
+    models: Res<ModelAssets>,
+    mut quiz: ResMut<Quiz>,
+    mut state: ResMut<State<GameState>>,
+) {
+    let quiz = &mut *quiz;
+
+    egui::Window::new("Spawn quiz").show(egui_context.ctx_mut(), |ui| {
+        ui.label(format!(
+            "Level {} of {}   {}/{} correct ({:.0}%)   {:.2}s average",
+            quiz.level + 1,
+            LEVELS.len(),
+            quiz.correct,
+            quiz.rounds,
+            quiz.accuracy(),
+            quiz.average_seconds()
+        ));
+        ui.separator();
+
+        match quiz.phase {
+            QuizPhase::Showing(_) => {
+                ui.label("Read the spawn...");
+            }
+            QuizPhase::Answering(_) => {
+                egui::Grid::new("quiz_spawns").show(ui, |ui| {
+                    for (i, spawn) in SPAWN_POINTS.iter().enumerate() {
+                        let picked = quiz.picked.contains(spawn);
+                        if ui.selectable_label(picked, spawn.label()).clicked() {
+                            if picked {
+                                quiz.picked.retain(|other| other != spawn);
+                            } else {
+                                quiz.picked.push(*spawn);
+                            }
+                        }
+                        if i % SPAWN_COLUMNS == SPAWN_COLUMNS - 1 {
+                            ui.end_row();
+                        }
+                    }
+                });
+
+                let input = ui
+                    .add(egui::TextEdit::singleline(&mut quiz.typed).hint_text("or type N1 N3 S2"));
+                if std::mem::take(&mut quiz.focus_answer) {
+                    input.request_focus();
+                }
+                let entered = input.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+
+                if ui.button("Submit").clicked() || entered {
+                    match quiz.answer() {
+                        Ok(answer) => quiz.submit(answer, time.seconds_since_startup()),
+                        Err(err) => quiz.error = Some(err),
+                    }
+                }
+                if let Some(err) = &quiz.error {
+                    ui.colored_label(egui::Color32::RED, err);
+                }
+            }
+            QuizPhase::Answered { correct, seconds } => {
+                if correct {
+                    ui.colored_label(egui::Color32::GREEN, format!("Correct in {:.2}s", seconds));
+                } else {
+                    ui.colored_label(
+                        egui::Color32::RED,
+                        format!("Wrong, it was {}", format_spawns(&quiz.spawns)),
+                    );
+                }
+                if ui.button("Next").clicked() {
+                    start_round(&mut commands, &models, quiz);
+                }
+            }
+        }
+
+        ui.separator();
+        if ui.button("Stop quiz").clicked() {
+            state.pop().unwrap();
+        }
+    });
+}
+
+fn despawn_quiz_nylos(mut commands: Commands, query: Query<Entity, With<QuizNylo>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spawn_point::{SPAWN_POINT_N1, SPAWN_POINT_N3, SPAWN_POINT_S2};
+
+    fn answering(spawns: Vec<SpawnPoint>, since: f64) -> Quiz {
+        Quiz {
+            spawns,
+            phase: QuizPhase::Answering(since),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn typed_answers_win_over_clicked_ones() {
+        let mut quiz = Quiz {
+            picked: vec![SPAWN_POINT_S2, SPAWN_POINT_N1],
+            ..Default::default()
+        };
+        assert_eq!(quiz.answer(), Ok(vec![SPAWN_POINT_N1, SPAWN_POINT_S2]));
+
+        quiz.typed = "n3".to_string();
+        assert_eq!(quiz.answer(), Ok(vec![SPAWN_POINT_N3]));
+
+        quiz.typed = "N1 N9".to_string();
+        assert!(quiz.answer().is_err());
+    }
+
+    #[test]
+    fn correct_streaks_move_up_a_level() {
+        let spawns = vec![SPAWN_POINT_N1, SPAWN_POINT_S2];
+        let mut quiz = answering(spawns.clone(), 1.0);
+
+        quiz.submit(spawns.clone(), 2.5);
+        assert!(matches!(
+            quiz.phase,
+            QuizPhase::Answered { correct: true, seconds } if seconds == 1.5
+        ));
+        assert_eq!((quiz.level, quiz.streak), (0, 1));
+
+        quiz.phase = QuizPhase::Answering(3.0);
+        quiz.submit(spawns, 3.5);
+        assert_eq!((quiz.level, quiz.streak), (1, 0));
+        assert_eq!((quiz.rounds, quiz.correct), (2, 2));
+        assert_eq!(quiz.average_seconds(), 1.0);
+    }
+
+    #[test]
+    fn wrong_answers_move_down_a_level() {
+        let mut quiz = answering(vec![SPAWN_POINT_N1, SPAWN_POINT_S2], 0.0);
+        quiz.level = 2;
+        quiz.streak = 1;
+
+        quiz.submit(vec![SPAWN_POINT_N1], 1.0);
+        assert!(matches!(
+            quiz.phase,
+            QuizPhase::Answered { correct: false, .. }
+        ));
+        assert_eq!((quiz.level, quiz.streak), (1, 0));
+        assert_eq!((quiz.rounds, quiz.correct), (1, 0));
+    }
+
+    #[test]
+    fn answers_only_count_while_answering() {
+        let mut quiz = answering(vec![SPAWN_POINT_N1], 0.0);
+        quiz.phase = QuizPhase::Answered {
+            correct: true,
+            seconds: 1.0,
+        };
+
+        quiz.submit(vec![SPAWN_POINT_N1], 2.0);
+        assert_eq!(quiz.rounds, 0);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::config::Config;
+use crate::matomenos::{CurrentSpawn, Matomenos, NylocasClicked};
+use crate::mode::Mode;
+use crate::player::Player;
+use crate::scenario::Scenario;
+use crate::spawn_point::SpawnPoint;
+use crate::spell::Spell;
+use crate::GameState;
+use bevy::prelude::*;
+
+pub struct ReplayPlugin;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ReplayCast {
+    pub tick: u32,
+    pub target: SpawnPoint,
+}
+
+// A recorded wave: the scenario code on the first line, then one "<tick> <spawn>" line per cast
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub scenario: Scenario,
+    pub casts: Vec<ReplayCast>,
+}
+
+#[derive(Default)]
+pub struct ReplayPlayback {
+    pub replay: Option<Replay>,
+    next: usize,
+}
+
+impl ReplayPlayback {
+    // Index of the next cast to play, kept in rewind snapshots so playback resumes from there
+    pub fn next(&self) -> usize {
+        self.next
+    }
+
+    pub fn set_next(&mut self, next: usize) {
+        self.next = next;
+    }
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplayPlayback>()
+            .add_system_set(SystemSet::on_update(GameState::Spawned).with_system(play_replay))
+            .add_system_set(SystemSet::on_update(GameState::Paused).with_system(play_replay))
+            .add_system_set(SystemSet::on_exit(GameState::Spawned).with_system(rewind_replay));
+    }
+}
+
+impl Replay {
+    pub fn parse(input: &str) -> Result<Replay, String> {
+        let mut lines = input.lines().map(str::trim).filter(|line| !line.is_empty());
+
+        // Saved replays carry a share code, so the mode here only matters for hand-written ones
+        let scenario = Scenario::parse(lines.next().ok_or("Replay is empty")?, Mode::Normal)?;
+
+        let mut casts = vec![];
+        for line in lines {
+            let (tick, target) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| format!("Invalid replay line \"{}\"", line))?;
+            let tick = tick
+                .parse()
+                .map_err(|_| format!("Invalid replay tick \"{}\"", tick))?;
+            let target = SpawnPoint::from_label(target.trim())
+                .ok_or_else(|| format!("Unknown spawn \"{}\"", target.trim()))?;
+            casts.push(ReplayCast { tick, target });
+        }
+
+        Ok(Replay { scenario, casts })
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = self.scenario.to_code();
+        for cast in &self.casts {
+            text.push_str(&format!("\n{} {}", cast.tick, cast.target.label()));
+        }
+        text.push('\n');
+        text
+    }
+}
+
+// Re-issues recorded casts on their ticks, as long as the recorded wave is the one being
+// played. The mode has to match too, since nylo hitpoints and so the freezes depend on it.
+fn play_replay(
+    mut playback: ResMut<ReplayPlayback>,
+    config: Res<Config>,
+    current_spawn: Res<CurrentSpawn>,
+    nylos_query: Query<(Entity, &Matomenos)>,
+    mut spell_query: Query<&mut Spell>,
+    player_query: Query<&Player>,
+    mut clicks: EventWriter<NylocasClicked>,
+) {
+    let next = playback.next;
+    let cast = match &playback.replay {
+        Some(replay)
+            if replay.scenario.spawns == current_spawn.spawns()
+                && replay.scenario.delays == *current_spawn.delays()
+                && replay.scenario.mode == config.mode =>
+        {
+            match replay.casts.get(next) {
+                Some(cast) => *cast,
+                None => return,
+            }
+        }
+        _ => return,
+    };
+
+    if cast.tick > current_spawn.tick() || player_query.single().attack_delay != 0 {
+        return;
+    }
+
+    playback.next += 1;
+
+    if let Some((entity, _)) = nylos_query
+        .iter()
+        .find(|(_, nylo)| nylo.spawn() == cast.target)
+    {
+        spell_query.single_mut().is_active = true;
+        clicks.send(NylocasClicked(entity));
+    }
+}
+
+fn rewind_replay(mut playback: ResMut<ReplayPlayback>) {
+    playback.next = 0;
+}
@@ -0,0 +1,167 @@
+use crate::blood::Blood;
+use crate::blood_spawn::{spawn_blood_spawn, BloodSpawn, BloodSpawnAssets, BloodSpawnState};
+use crate::chat::Chat;
+use crate::clickbox::Clickbox;
+use crate::loading::ModelAssets;
+use crate::matomenos::{spawn_single_nylo, CurrentSpawn, Matomenos, NyloState, SpawnState};
+use crate::player::Player;
+use crate::replay::ReplayPlayback;
+use crate::spell::Spell;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+pub struct RewindPlugin;
+
+// The whole simulation at the start of a tick
+struct Snapshot {
+    tick: u32,
+    spawn: SpawnState,
+    nylos: Vec<NyloState>,
+    player_translation: Vec3,
+    player: Player,
+    blood: Blood,
+    blood_spawns: Vec<BloodSpawnState>,
+    replay_next: usize,
+}
+
+// Snapshots of the current wave in tick order. Ticks with no nylos up yet, such as
+// before a wave of delayed spawns comes up, have none.
+#[derive(Default)]
+pub struct Timeline {
+    snapshots: Vec<Snapshot>,
+    last_tick: Option<u32>,
+}
+
+impl Plugin for RewindPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Timeline>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Spawned)
+                    .with_system(take_snapshot)
+                    .with_system(timeline_ui),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Paused)
+                    .with_system(take_snapshot)
+                    .with_system(timeline_ui),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Spawned).with_system(clear_timeline));
+    }
+}
+
+fn take_snapshot(
+    mut timeline: ResMut<Timeline>,
+    current_spawn: Res<CurrentSpawn>,
+    blood: Res<Blood>,
+    playback: Res<ReplayPlayback>,
+    nylos_query: Query<(&Transform, &Matomenos)>,
+    blood_spawns_query: Query<(&Transform, &BloodSpawn)>,
+    player_query: Query<(&Transform, &Player)>,
+) {
+    let tick = current_spawn.tick();
+    if !current_spawn.is_spawned() || timeline.last_tick == Some(tick) || nylos_query.is_empty() {
+        return;
+    }
+
+    let (player_transform, player) = player_query.single();
+
+    // Anything recorded from this tick on belongs to a timeline that was rewound over
+    timeline.snapshots.retain(|snapshot| snapshot.tick < tick);
+    timeline.snapshots.push(Snapshot {
+        tick,
+        spawn: current_spawn.state(),
+        nylos: nylos_query
+            .iter()
+            .map(|(transform, nylo)| nylo.state(transform))
+            .collect(),
+        player_translation: player_transform.translation,
+        player: player.clone(),
+        blood: blood.clone(),
+        blood_spawns: blood_spawns_query
+            .iter()
+            .map(|(transform, blood_spawn)| blood_spawn.state(transform))
+            .collect(),
+        replay_next: playback.next(),
+    });
+    timeline.last_tick = Some(tick);
+}
+
+fn timeline_ui(
+    mut commands: Commands,
+    mut egui_context: ResMut<EguiContext>,
+    mut timeline: ResMut<Timeline>,
+    mut current_spawn: ResMut<CurrentSpawn>,
+    mut blood: ResMut<Blood>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut chat: ResMut<Chat>,
+    clickbox: Res<Clickbox>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    models: Res<ModelAssets>,
+    blood_spawn_assets: Res<BloodSpawnAssets>,
+    nylos_query: Query<Entity, With<Matomenos>>,
+    blood_spawns_query: Query<Entity, With<BloodSpawn>>,
+    mut player_query: Query<(&mut Transform, &mut Player)>,
+    mut spell_query: Query<&mut Spell>,
+) {
+    let (first, latest) = match (timeline.snapshots.first(), timeline.snapshots.last()) {
+        (Some(first), Some(latest)) => (first.tick, latest.tick),
+        _ => return,
+    };
+    let mut selected = current_spawn.tick().clamp(first, latest);
+    let mut changed = false;
+
+    egui::Window::new("Timeline").show(egui_context.ctx_mut(), |ui| {
+        changed = ui
+            .add(egui::Slider::new(&mut selected, first..=latest).text("Tick"))
+            .changed();
+    });
+
+    if !changed {
+        return;
+    }
+
+    // A tick without a snapshot goes back to the closest one before it
+    let snapshot = match timeline
+        .snapshots
+        .iter()
+        .rev()
+        .find(|snapshot| snapshot.tick <= selected)
+    {
+        Some(snapshot) => snapshot,
+        None => return,
+    };
+    let tick = snapshot.tick;
+
+    for entity in nylos_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for nylo in &snapshot.nylos {
+        spawn_single_nylo(&mut commands, &clickbox, &mut materials, &models, nylo);
+    }
+    for entity in blood_spawns_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for blood_spawn in &snapshot.blood_spawns {
+        spawn_blood_spawn(&mut commands, &blood_spawn_assets, blood_spawn);
+    }
+
+    current_spawn.restore(&snapshot.spawn);
+
+    let (mut player_transform, mut player) = player_query.single_mut();
+    player_transform.translation = snapshot.player_translation;
+    *player = snapshot.player.clone();
+    *blood = snapshot.blood.clone();
+    playback.set_next(snapshot.replay_next);
+    // Messages are stamped with the tick they were sent on, which is all it takes to roll them back
+    chat.rewind(tick);
+    spell_query.single_mut().is_active = false;
+
+    // Live play resumes from here, and the next tick replaces the later snapshots
+    timeline.last_tick = Some(tick);
+}
+
+fn clear_timeline(mut timeline: ResMut<Timeline>) {
+    timeline.snapshots.clear();
+    timeline.last_tick = None;
+}
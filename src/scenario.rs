@@ -0,0 +1,283 @@
+use crate::mode::Mode;
+use crate::spawn_point::{
+    format_delayed_spawns, parse_spawns, SpawnDelays, SpawnPoint, MAX_SPAWN_DELAY, SPAWN_POINTS,
+};
+
+// Normal mode codes stay on the versions before the mode was added, and those without
+// delayed spawns on the first one, so they still load in older builds
+const CODE_VERSION: u8 = 3;
+const DELAYED_CODE_VERSION: u8 = 2;
+const UNDELAYED_CODE_VERSION: u8 = 1;
+// Largest team the room allows, and so the largest player count a code can carry
+pub const MAX_PLAYERS: usize = 5;
+
+const CODE_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+// Everything needed to replay a specific wave, shareable as chat notation or a short code
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scenario {
+    pub players: usize,
+    pub spawns: Vec<SpawnPoint>,
+    pub delays: SpawnDelays,
+    pub mode: Mode,
+}
+
+impl Scenario {
+    // Accepts either chat notation ("N1 N3 S2 S4w"), which is played in the given mode,
+    // or a share code, which carries its own
+    pub fn parse(input: &str, mode: Mode) -> Result<Scenario, String> {
+        let input = input.trim();
+        match parse_spawns(input) {
+            Ok((spawns, delays)) => Ok(Scenario {
+                players: spawns.len().div_ceil(2).clamp(1, MAX_PLAYERS),
+                spawns,
+                delays,
+                mode,
+            }),
+            Err(notation_err) => Scenario::from_code(input).map_err(|code_err| {
+                if input.contains(char::is_whitespace) {
+                    notation_err
+                } else {
+                    code_err
+                }
+            }),
+        }
+    }
+
+    pub fn notation(&self) -> String {
+        format_delayed_spawns(&self.spawns, &self.delays)
+    }
+
+    pub fn to_code(&self) -> String {
+        let mask = self
+            .spawns
+            .iter()
+            .filter_map(SpawnPoint::index)
+            .fold(0u16, |mask, i| mask | 1 << i);
+        let [mask_lo, mask_hi] = mask.to_le_bytes();
+
+        let mut bytes = match self.mode {
+            Mode::Normal if self.delays.is_empty() => {
+                return encode(&[UNDELAYED_CODE_VERSION, self.players as u8, mask_lo, mask_hi]);
+            }
+            Mode::Normal => vec![DELAYED_CODE_VERSION, self.players as u8, mask_lo, mask_hi],
+            mode => vec![
+                CODE_VERSION,
+                self.players as u8,
+                mask_lo,
+                mask_hi,
+                mode.index() as u8,
+            ],
+        };
+
+        // One delay per spawn, in SPAWN_POINTS order
+        let mut spawns = self.spawns.clone();
+        spawns.sort_by_key(SpawnPoint::index);
+        bytes.extend(spawns.iter().map(|spawn| self.delays.get(*spawn) as u8));
+        encode(&bytes)
+    }
+
+    pub fn from_code(code: &str) -> Result<Scenario, String> {
+        let bytes = decode(code).ok_or_else(|| "Invalid share code".to_string())?;
+
+        match bytes.as_slice() {
+            [version @ (UNDELAYED_CODE_VERSION..=CODE_VERSION), players, mask_lo, mask_hi, rest @ ..] =>
+            {
+                let mask = u16::from_le_bytes([*mask_lo, *mask_hi]);
+                let spawns: Vec<SpawnPoint> = SPAWN_POINTS
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| mask & (1 << i) != 0)
+                    .map(|(_, spawn)| *spawn)
+                    .collect();
+
+                if spawns.is_empty() {
+                    return Err("Invalid share code".to_string());
+                }
+                if !(1..=MAX_PLAYERS).contains(&(*players as usize)) {
+                    return Err(format!("Invalid player count {} in share code", players));
+                }
+
+                let (mode, rest) = match (*version, rest) {
+                    (CODE_VERSION, [mode, rest @ ..]) => (
+                        Mode::from_index(*mode as usize)
+                            .ok_or_else(|| format!("Invalid mode {} in share code", mode))?,
+                        rest,
+                    ),
+                    (CODE_VERSION, []) => return Err("Invalid share code".to_string()),
+                    _ => (Mode::Normal, rest),
+                };
+
+                let mut delays = SpawnDelays::default();
+                if *version != UNDELAYED_CODE_VERSION {
+                    if rest.len() < spawns.len() {
+                        return Err("Invalid share code".to_string());
+                    }
+                    for (spawn, delay) in spawns.iter().zip(rest) {
+                        if *delay as u32 > MAX_SPAWN_DELAY {
+                            return Err("Invalid share code".to_string());
+                        }
+                        delays.set(*spawn, *delay as u32);
+                    }
+                }
+
+                Ok(Scenario {
+                    players: *players as usize,
+                    spawns,
+                    delays,
+                    mode,
+                })
+            }
+            [version, ..] if *version > CODE_VERSION => {
+                Err("Share code is from a newer version".to_string())
+            }
+            _ => Err("Invalid share code".to_string()),
+        }
+    }
+}
+
+// URL-safe base64 without padding, so codes survive chat and links untouched
+fn encode(bytes: &[u8]) -> String {
+    let mut code = String::new();
+
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+
+        for i in 0..=chunk.len() {
+            let sextet = (n >> (18 - 6 * i)) & 0x3f;
+            code.push(CODE_ALPHABET[sextet as usize] as char);
+        }
+    }
+
+    code
+}
+
+fn decode(code: &str) -> Option<Vec<u8>> {
+    let sextets = code
+        .bytes()
+        .map(|c| CODE_ALPHABET.iter().position(|a| *a == c).map(|i| i as u32))
+        .collect::<Option<Vec<u32>>>()?;
+
+    let mut bytes = vec![];
+
+    for chunk in sextets.chunks(4) {
+        if chunk.len() < 2 {
+            return None;
+        }
+
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, s)| n | s << (18 - 6 * i));
+
+        for i in 0..chunk.len() - 1 {
+            bytes.push((n >> (16 - 8 * i)) as u8);
+        }
+    }
+
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenario(notation: &str) -> Scenario {
+        Scenario::parse(notation, Mode::Normal).unwrap()
+    }
+
+    #[test]
+    fn codes_round_trip() {
+        for notation in [
+            "N1",
+            "N1 N3 S2 S4w",
+            "N4w S1+2 S3+10",
+            "N1 N2 N3 N4 N4w S1 S2 S3 S4 S4w",
+        ] {
+            let scenario = scenario(notation);
+            assert_eq!(
+                Scenario::parse(&scenario.to_code(), Mode::Hard),
+                Ok(scenario.clone())
+            );
+            assert_eq!(scenario.notation(), notation);
+        }
+    }
+
+    #[test]
+    fn codes_carry_the_mode() {
+        for mode in [Mode::Entry, Mode::Hard] {
+            for notation in ["N1 S2", "N4w S1+2"] {
+                let scenario = Scenario::parse(notation, mode).unwrap();
+                assert_eq!(scenario.mode, mode);
+                assert_eq!(
+                    Scenario::parse(&scenario.to_code(), Mode::Normal),
+                    Ok(scenario)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn normal_codes_stay_on_older_versions() {
+        let undelayed = decode(&scenario("N1 S2").to_code()).unwrap();
+        assert_eq!(undelayed[0], UNDELAYED_CODE_VERSION);
+
+        let delayed = decode(&scenario("N1 S2+1").to_code()).unwrap();
+        assert_eq!(delayed[0], DELAYED_CODE_VERSION);
+
+        let hard = Scenario::parse("N1 S2", Mode::Hard).unwrap();
+        assert_eq!(decode(&hard.to_code()).unwrap()[0], CODE_VERSION);
+    }
+
+    #[test]
+    fn reads_first_version_codes() {
+        // Three players, N1 and S2
+        let code = encode(&[UNDELAYED_CODE_VERSION, 3, 0b0100_0001, 0]);
+        assert_eq!(
+            Scenario::from_code(&code),
+            Ok(Scenario {
+                players: 3,
+                spawns: vec![SPAWN_POINTS[0], SPAWN_POINTS[6]],
+                delays: SpawnDelays::default(),
+                mode: Mode::Normal,
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_bad_codes() {
+        // Outside the alphabet
+        assert!(Scenario::from_code("AQ*FAg").is_err());
+        // A second version code missing the delay for its second spawn
+        let truncated = encode(&[DELAYED_CODE_VERSION, 1, 0b0000_0011, 0, 1]);
+        assert!(Scenario::from_code(&truncated).is_err());
+        // Delay past the limit
+        let late = encode(&[
+            DELAYED_CODE_VERSION,
+            1,
+            0b0000_0001,
+            0,
+            MAX_SPAWN_DELAY as u8 + 1,
+        ]);
+        assert!(Scenario::from_code(&late).is_err());
+        // A third version code missing its mode, or with a mode past the last one
+        assert!(Scenario::from_code(&encode(&[CODE_VERSION, 1, 1, 0])).is_err());
+        assert!(Scenario::from_code(&encode(&[CODE_VERSION, 1, 1, 0, 3, 0])).is_err());
+        // No spawns
+        assert!(Scenario::from_code(&encode(&[UNDELAYED_CODE_VERSION, 1, 0, 0])).is_err());
+        // From a newer build
+        assert!(Scenario::from_code(&encode(&[CODE_VERSION + 1, 1, 1, 0])).is_err());
+    }
+
+    #[test]
+    fn rejects_player_counts_outside_the_room() {
+        for players in [0, MAX_PLAYERS as u8 + 1, 255] {
+            let code = encode(&[UNDELAYED_CODE_VERSION, players, 1, 0]);
+            assert!(Scenario::from_code(&code).is_err());
+        }
+    }
+}
@@ -0,0 +1,547 @@
+use crate::map::MAP_WIDTH;
+use crate::spawn_point::{SpawnDelays, SpawnPoint};
+use rand::Rng;
+
+// Ticks the player has to wait after casting Ice Barrage
+pub const CAST_DELAY: i32 = 5;
+
+// Ice Barrage can be cast on targets up to this many tiles away
+pub const CAST_RANGE: f32 = 10.0;
+
+// Chance of a barrage splashing on a nylo in its area. Nylos have next to no magic defence.
+pub const BARRAGE_SPLASH_CHANCE: f64 = 0.05;
+
+// The barrage's max hit, the ticks it holds a nylo, and the ticks after that the nylo
+// can't be frozen again. These are the player's spell, so they're the same in every mode.
+pub const BARRAGE_MAX_HIT: u32 = 30;
+pub const FREEZE_TICKS: u32 = 32;
+pub const IMMUNITY_TICKS: u32 = 5;
+
+// Hitpoints of a nylo in a full team, which smaller teams get a share of
+pub const NYLO_HITPOINTS: u32 = 100;
+
+// Maiden's Southwest Tile, which every nylo walks towards
+const MAIDEN_X: f32 = 1.5;
+const MAIDEN_Z: f32 = 13.5;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FrozenState {
+    NotFrozen,
+    ShouldFreeze,
+    // Ticks left frozen
+    Frozen(u32),
+    // Ticks left until it can be frozen again
+    Immune(u32),
+}
+
+// What a barrage does to each nylo in its area
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BarrageHit {
+    // Freezes the nylo, with the damage landing on the next tick
+    Hit(u32),
+    // Neither freezes nor hurts the nylo, which keeps walking
+    Splash,
+}
+
+impl BarrageHit {
+    pub fn roll<R: Rng>(rng: &mut R) -> BarrageHit {
+        if rng.gen_bool(BARRAGE_SPLASH_CHANCE) {
+            BarrageHit::Splash
+        } else {
+            BarrageHit::Hit(rng.gen_range(1..=BARRAGE_MAX_HIT))
+        }
+    }
+
+    pub fn damage(&self) -> u32 {
+        match self {
+            BarrageHit::Hit(damage) => *damage,
+            BarrageHit::Splash => 0,
+        }
+    }
+}
+
+pub enum NyloStep {
+    Stay,
+    Move(f32, f32),
+    Leak,
+}
+
+// Advances a single nylo by one tick. A nylo hit by a barrage still takes
+// its next step before the freeze lands, so it can leak on that step.
+pub fn step_nylo(frozen: &mut FrozenState, x: f32, z: f32) -> NyloStep {
+    match *frozen {
+        FrozenState::Frozen(ticks) => {
+            *frozen = match ticks {
+                0 | 1 => FrozenState::Immune(IMMUNITY_TICKS),
+                _ => FrozenState::Frozen(ticks - 1),
+            };
+            return NyloStep::Stay;
+        }
+        FrozenState::ShouldFreeze => {
+            *frozen = FrozenState::Frozen(FREEZE_TICKS);
+        }
+        FrozenState::Immune(ticks) => {
+            *frozen = match ticks {
+                0 | 1 => FrozenState::NotFrozen,
+                _ => FrozenState::Immune(ticks - 1),
+            };
+        }
+        FrozenState::NotFrozen => {}
+    };
+
+    let (x, z) = next_position(x, z);
+
+    if reaches_maiden(x, z) {
+        NyloStep::Leak
+    } else {
+        NyloStep::Move(x, z)
+    }
+}
+
+pub fn next_position(x: f32, z: f32) -> (f32, f32) {
+    // Distance to Maiden's Southwest Tile
+    let distance_x = MAIDEN_X - x;
+    let distance_z = MAIDEN_Z - z;
+
+    // Calculate next tick's X and Z coordinates
+    // - If there is no difference between Maiden's X/Z and the
+    //   nylo's X/Z coordinate, maintain it
+    // - If there is a difference, use the signum function to add
+    //   or subtract 1 unit in that direction
+    let x = if distance_x == 0.0 {
+        x
+    } else {
+        x + distance_x.signum() * 1.0
+    };
+
+    let z = if distance_z == 0.0 {
+        z
+    } else {
+        z + distance_z.signum() * 1.0
+    };
+
+    (x, z)
+}
+
+// Whether a nylo standing on this tile has run into Maiden
+pub fn reaches_maiden(x: f32, z: f32) -> bool {
+    (2.0..=8.0).contains(&x) && (8.0..=MAP_WIDTH as f32 - 9.0).contains(&z)
+}
+
+// Ice Barrage hits every nylo in the 3x3 area centred on its target
+pub fn in_barrage_area(target: (f32, f32), position: (f32, f32)) -> bool {
+    f32::abs(target.0 - position.0) <= 1.0 && f32::abs(target.1 - position.1) <= 1.0
+}
+
+// The closest tile to the player that is within casting range of the target
+pub fn tile_in_range(player: (f32, f32), target: (f32, f32)) -> (f32, f32) {
+    let clamp = |from: f32, to: f32| {
+        let tile = to + (from - to).clamp(-CAST_RANGE, CAST_RANGE);
+        tile.floor() + 0.5
+    };
+    (clamp(player.0, target.0), clamp(player.1, target.1))
+}
+
+// Barrages that caught more than one nylo. Catching nylos stacked on a single
+// tile is credited apart from catching nylos spread over several tiles.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct MultiFreezes {
+    pub spread: u32,
+    pub stacked: u32,
+}
+
+impl MultiFreezes {
+    // Credits a barrage given the tiles of the nylos it caught
+    pub fn credit(&mut self, caught: &[(f32, f32)]) {
+        let mut tiles = caught.to_vec();
+        tiles.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        tiles.dedup();
+
+        if tiles.len() > 1 {
+            self.spread += 1;
+        } else if caught.len() > 1 {
+            self.stacked += 1;
+        }
+    }
+}
+
+// Whether a nylo can step onto a tile, given the tiles the other nylos stand on
+pub fn can_enter(
+    stacking: bool,
+    tile: (f32, f32),
+    mut occupied: impl Iterator<Item = (f32, f32)>,
+) -> bool {
+    stacking || !occupied.any(|other| other == tile)
+}
+
+#[derive(Clone, Debug)]
+pub struct SimNylo {
+    pub spawn: SpawnPoint,
+    pub x: f32,
+    pub z: f32,
+    pub frozen: FrozenState,
+    pub leaked: bool,
+    // Tick the nylo comes up on, later than the rest of the wave for a delayed spawn
+    pub spawn_tick: u32,
+    pub hitpoints: u32,
+    // Damage from a barrage, taken on the next tick
+    pub pending_damage: Option<u32>,
+    pub dead: bool,
+}
+
+impl SimNylo {
+    // Whether the nylo has left the room, by leaking or dying
+    pub fn is_gone(&self) -> bool {
+        self.leaked || self.dead
+    }
+}
+
+// A headless wave following the same tick rules as the game: casts happen
+// between ticks, and every tick counts down the cast delay and moves the
+// nylos, except the first one, which the wave spends standing where it came up.
+#[derive(Clone, Debug)]
+pub struct Wave {
+    pub nylos: Vec<SimNylo>,
+    pub tick: u32,
+    // Whether the wave has yet to take its first step. The tick it stands still
+    // for isn't counted, so the first step is still tick 1.
+    pub standing: bool,
+    pub attack_delay: i32,
+    pub casts: u32,
+    pub leaks: u32,
+    pub multi_freezes: MultiFreezes,
+    // Whether nylos can share a tile, or wait for it to clear
+    pub stacking: bool,
+}
+
+impl Wave {
+    pub fn new(spawns: &[SpawnPoint]) -> Wave {
+        Wave {
+            nylos: spawns
+                .iter()
+                .map(|spawn| SimNylo {
+                    spawn: *spawn,
+                    x: spawn.x,
+                    z: spawn.y,
+                    frozen: FrozenState::NotFrozen,
+                    leaked: false,
+                    spawn_tick: 0,
+                    hitpoints: NYLO_HITPOINTS,
+                    pending_damage: None,
+                    dead: false,
+                })
+                .collect(),
+            tick: 0,
+            standing: true,
+            attack_delay: 0,
+            casts: 0,
+            leaks: 0,
+            multi_freezes: MultiFreezes::default(),
+            stacking: true,
+        }
+    }
+
+    pub fn delay_spawns(&mut self, delays: &SpawnDelays) {
+        for nylo in self.nylos.iter_mut() {
+            nylo.spawn_tick = delays.get(nylo.spawn);
+        }
+    }
+
+    pub fn set_hitpoints(&mut self, hitpoints: u32) {
+        for nylo in self.nylos.iter_mut() {
+            nylo.hitpoints = hitpoints;
+        }
+    }
+
+    // Nylos that have come up, are still walking and haven't been hit yet
+    pub fn unfrozen(&self) -> impl Iterator<Item = (usize, &SimNylo)> {
+        self.nylos.iter().enumerate().filter(|(_, nylo)| {
+            nylo.spawn_tick <= self.tick && !nylo.is_gone() && nylo.frozen == FrozenState::NotFrozen
+        })
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.unfrozen().next().is_none()
+            && self
+                .nylos
+                .iter()
+                .all(|nylo| nylo.is_gone() || matches!(nylo.frozen, FrozenState::Frozen(_)))
+    }
+
+    pub fn can_cast(&self) -> bool {
+        self.attack_delay == 0
+    }
+
+    // Indices of the unfrozen nylos a barrage on the target would catch
+    pub fn barrage_targets(&self, target: usize) -> Vec<usize> {
+        let target = &self.nylos[target];
+        self.unfrozen()
+            .filter(|(_, nylo)| in_barrage_area((target.x, target.z), (nylo.x, nylo.z)))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    // Casts on the target, rolling a hit for every nylo in the area, and
+    // returns the nylos caught. Like the game, a barrage with nothing in its
+    // area is not a cast, and nylos it splashes on aren't caught.
+    pub fn cast(&mut self, target: usize, mut roll: impl FnMut() -> BarrageHit) -> Vec<usize> {
+        if !self.can_cast() || self.nylos[target].is_gone() {
+            return vec![];
+        }
+
+        let targets = self.barrage_targets(target);
+        if targets.is_empty() {
+            return vec![];
+        }
+
+        let mut caught = vec![];
+        for i in targets {
+            if let BarrageHit::Hit(damage) = roll() {
+                self.nylos[i].frozen = FrozenState::ShouldFreeze;
+                self.nylos[i].pending_damage = Some(damage);
+                caught.push(i);
+            }
+        }
+        self.attack_delay = CAST_DELAY;
+        self.casts += 1;
+        let tiles: Vec<(f32, f32)> = caught
+            .iter()
+            .map(|i| (self.nylos[*i].x, self.nylos[*i].z))
+            .collect();
+        self.multi_freezes.credit(&tiles);
+
+        caught
+    }
+
+    pub fn step(&mut self) {
+        self.attack_delay = std::cmp::max(0, self.attack_delay - 1);
+        if self.standing {
+            self.standing = false;
+            return;
+        }
+        self.tick += 1;
+
+        for i in 0..self.nylos.len() {
+            // A delayed nylo stands still on the tick it comes up, like the rest of the wave did
+            if self.nylos[i].is_gone() || self.nylos[i].spawn_tick >= self.tick {
+                continue;
+            }
+            let nylo = &mut self.nylos[i];
+            if let Some(damage) = nylo.pending_damage.take() {
+                nylo.hitpoints = nylo.hitpoints.saturating_sub(damage);
+            }
+            if nylo.hitpoints == 0 {
+                nylo.dead = true;
+                continue;
+            }
+            match step_nylo(&mut nylo.frozen, nylo.x, nylo.z) {
+                NyloStep::Stay => {}
+                NyloStep::Move(x, z) => {
+                    let occupied = self
+                        .nylos
+                        .iter()
+                        .enumerate()
+                        .filter(|(j, other)| {
+                            *j != i && !other.is_gone() && other.spawn_tick <= self.tick
+                        })
+                        .map(|(_, other)| (other.x, other.z));
+                    if can_enter(self.stacking, (x, z), occupied) {
+                        self.nylos[i].x = x;
+                        self.nylos[i].z = z;
+                    }
+                }
+                NyloStep::Leak => {
+                    self.nylos[i].leaked = true;
+                    self.leaks += 1;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Strategy {
+    // Target whichever nylo catches the most unfrozen nylos
+    Greedy,
+    // Target the unfrozen nylo closest to Maiden
+    Closest,
+    // Freeze north spawns before south spawns, in spawn order
+    NorthFirst,
+    // Freeze south spawns before north spawns, in spawn order
+    SouthFirst,
+    // Target a random unfrozen nylo
+    Random,
+}
+
+pub const STRATEGIES: &[(&str, Strategy)] = &[
+    ("greedy", Strategy::Greedy),
+    ("closest", Strategy::Closest),
+    ("north-first", Strategy::NorthFirst),
+    ("south-first", Strategy::SouthFirst),
+    ("random", Strategy::Random),
+];
+
+impl Strategy {
+    pub fn from_name(name: &str) -> Option<Strategy> {
+        STRATEGIES
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, strategy)| *strategy)
+    }
+
+    pub fn choose<R: Rng>(&self, wave: &Wave, rng: &mut R) -> Option<usize> {
+        let unfrozen: Vec<usize> = wave.unfrozen().map(|(i, _)| i).collect();
+        if unfrozen.is_empty() {
+            return None;
+        }
+
+        let distance = |i: &usize| {
+            let nylo = &wave.nylos[*i];
+            f32::max((nylo.x - MAIDEN_X).abs(), (nylo.z - MAIDEN_Z).abs()) as i32
+        };
+
+        match self {
+            Strategy::Greedy => unfrozen
+                .iter()
+                .copied()
+                .max_by_key(|i| (wave.barrage_targets(*i).len(), -distance(i))),
+            Strategy::Closest => unfrozen.iter().copied().min_by_key(distance),
+            Strategy::NorthFirst => unfrozen
+                .iter()
+                .copied()
+                .min_by_key(|i| wave.nylos[*i].spawn.index()),
+            Strategy::SouthFirst => unfrozen
+                .iter()
+                .copied()
+                .min_by_key(|i| (wave.nylos[*i].spawn.y < 10.0, wave.nylos[*i].spawn.index())),
+            Strategy::Random => Some(unfrozen[rng.gen_range(0..unfrozen.len())]),
+        }
+    }
+
+    // Plays a whole wave, casting whenever the strategy has a target and the delay allows
+    pub fn play<R: Rng>(&self, wave: &mut Wave, rng: &mut R) {
+        while !wave.is_finished() {
+            if wave.can_cast() {
+                if let Some(target) = self.choose(wave, rng) {
+                    wave.cast(target, || BarrageHit::roll(rng));
+                }
+            }
+            wave.step();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spawn_point::{
+        format_delayed_spawns, generate_spawn_points, scuff_spawns, wave_rng, SPAWN_POINT_N1,
+        SPAWN_POINT_N2, SPAWN_POINT_N3, SPAWN_POINT_N4_WALL, SPAWN_POINT_S4,
+    };
+
+    // Plays a wave casting on the first nylo still walking whenever the delay allows,
+    // noting the tick, target and catch of every cast
+    fn play_first_target(wave: &mut Wave) -> Vec<(u32, SpawnPoint, usize)> {
+        let mut casts = vec![];
+        while !wave.is_finished() {
+            if wave.can_cast() {
+                let target = wave.unfrozen().next().map(|(i, _)| i);
+                if let Some(target) = target {
+                    let caught = wave.cast(target, || BarrageHit::Hit(0));
+                    casts.push((wave.tick, wave.nylos[target].spawn, caught.len()));
+                }
+            }
+            wave.step();
+        }
+        casts
+    }
+
+    #[test]
+    fn lone_nylos_leak_on_known_ticks() {
+        // N1 comes up closest to Maiden, S4 a long way round the south side
+        for (spawn, tick) in [(SPAWN_POINT_N1, 7), (SPAWN_POINT_S4, 18)] {
+            let mut wave = Wave::new(&[spawn]);
+            while !wave.is_finished() {
+                wave.step();
+            }
+            assert_eq!((wave.tick, wave.leaks), (tick, 1));
+        }
+    }
+
+    #[test]
+    fn seeded_wave_has_a_known_outcome() {
+        let mut rng = wave_rng(7, 3);
+        let spawns = generate_spawn_points(&mut rng, 6);
+        let delays = scuff_spawns(&mut rng, &spawns, 0.5, 2);
+        assert_eq!(
+            format_delayed_spawns(&spawns, &delays),
+            "N1 N2 N3 N4w S3+1 S4w+1"
+        );
+
+        let mut wave = Wave::new(&spawns);
+        wave.delay_spawns(&delays);
+        assert_eq!(
+            play_first_target(&mut wave),
+            vec![
+                (0, SPAWN_POINT_N1, 1),
+                (4, SPAWN_POINT_N2, 1),
+                (9, SPAWN_POINT_N3, 1),
+                (14, SPAWN_POINT_N4_WALL, 2),
+            ]
+        );
+
+        // S3 reaches Maiden before the last cast, which only catches N4w and S4w
+        assert_eq!((wave.tick, wave.leaks, wave.casts), (15, 1, 4));
+        assert!(wave.nylos[4].leaked);
+        assert_eq!(wave.nylos[5].frozen, FrozenState::Frozen(FREEZE_TICKS));
+    }
+
+    #[test]
+    fn splashed_nylos_keep_walking() {
+        let mut wave = Wave::new(&[
+            crate::spawn_point::SPAWN_POINT_N1,
+            crate::spawn_point::SPAWN_POINT_N1,
+        ]);
+        let mut hits = vec![BarrageHit::Splash, BarrageHit::Hit(10)].into_iter();
+
+        assert_eq!(wave.cast(0, || hits.next().unwrap()), vec![1]);
+        assert_eq!(wave.casts, 1);
+        assert_eq!(wave.attack_delay, CAST_DELAY);
+        assert_eq!(wave.nylos[0].frozen, FrozenState::NotFrozen);
+        assert_eq!(wave.nylos[0].pending_damage, None);
+        assert_eq!(wave.nylos[1].frozen, FrozenState::ShouldFreeze);
+        assert_eq!(wave.multi_freezes, MultiFreezes::default());
+    }
+
+    #[test]
+    fn nylos_die_when_the_damage_lands() {
+        let mut wave = Wave::new(&[crate::spawn_point::SPAWN_POINT_N1]);
+        wave.set_hitpoints(20);
+        wave.cast(0, || BarrageHit::Hit(BARRAGE_MAX_HIT));
+
+        // Standing still, so the damage waits for the first step
+        wave.step();
+        assert!(!wave.nylos[0].dead);
+
+        wave.step();
+        assert!(wave.nylos[0].dead);
+        assert_eq!(wave.nylos[0].hitpoints, 0);
+        assert_eq!(wave.leaks, 0);
+        assert!(wave.is_finished());
+    }
+
+    #[test]
+    fn first_tick_is_spent_standing() {
+        let mut wave = Wave::new(&[crate::spawn_point::SPAWN_POINT_N1]);
+        wave.attack_delay = CAST_DELAY;
+
+        wave.step();
+        assert_eq!(wave.tick, 0);
+        assert_eq!(wave.attack_delay, CAST_DELAY - 1);
+        assert_eq!((wave.nylos[0].x, wave.nylos[0].z), (13.5, 1.5));
+
+        wave.step();
+        assert_eq!(wave.tick, 1);
+        assert_ne!((wave.nylos[0].x, wave.nylos[0].z), (13.5, 1.5));
+    }
+}
@@ -0,0 +1,333 @@
+use crate::camera;
+use crate::config::Config;
+use crate::matomenos::CurrentSpawn;
+use crate::mode::Mode;
+use crate::overlay::{self, Projector};
+use crate::sim::{BarrageHit, FrozenState, Wave};
+use crate::spawn_point::{SpawnDelays, SpawnPoint};
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use std::collections::HashMap;
+
+// Wave states the search may visit before it settles for the best plan so far.
+// Big parties can have far more orderings than a frame has time for.
+const SEARCH_BUDGET: usize = 100_000;
+
+#[derive(Clone, Debug)]
+pub struct PlanStep {
+    pub tick: u32,
+    pub target: SpawnPoint,
+    // Where the target will be standing when the cast goes out
+    pub x: f32,
+    pub z: f32,
+    pub caught: Vec<SpawnPoint>,
+}
+
+impl PlanStep {
+    pub fn describe(&self) -> String {
+        let caught = self
+            .caught
+            .iter()
+            .map(SpawnPoint::label)
+            .collect::<Vec<_>>()
+            .join("+");
+        format!(
+            "T{}: cast on {}, catches {}",
+            self.tick,
+            self.target.label(),
+            caught
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Solution {
+    pub steps: Vec<PlanStep>,
+    pub leaks: u32,
+    pub ticks: u32,
+    // Whether every plan was searched, rather than the budget running out first
+    pub complete: bool,
+}
+
+impl Solution {
+    fn key(&self) -> (u32, usize, u32) {
+        (self.leaks, self.steps.len(), self.ticks)
+    }
+}
+
+// Finds the cast sequence with the fewest leaks, then the fewest casts, then
+// the earliest full freeze, by searching every cast target and timing. The
+// search stops at SEARCH_BUDGET states with the best plan found by then.
+pub fn solve(
+    spawns: &[SpawnPoint],
+    delays: &SpawnDelays,
+    stacking: bool,
+) -> Result<Solution, String> {
+    let mut wave = Wave::new(spawns);
+    wave.delay_spawns(delays);
+    wave.stacking = stacking;
+
+    let mut search = Search::default();
+    search.visit(wave, &mut vec![]);
+
+    let complete = search.nodes <= SEARCH_BUDGET;
+    search
+        .best
+        .map(|best| Solution { complete, ..best })
+        .ok_or_else(|| "No solution within the search budget".to_string())
+}
+
+#[derive(Default)]
+struct Search {
+    best: Option<Solution>,
+    // Fewest casts each wave state has been reached with
+    seen: HashMap<Vec<u32>, usize>,
+    nodes: usize,
+}
+
+// Everything about a wave that decides how it plays out from here
+fn state_key(wave: &Wave) -> Vec<u32> {
+    let mut key = vec![
+        wave.tick,
+        wave.attack_delay as u32,
+        wave.leaks,
+        wave.standing as u32,
+    ];
+    for nylo in &wave.nylos {
+        let (frozen, ticks) = match nylo.frozen {
+            FrozenState::NotFrozen => (0, 0),
+            FrozenState::ShouldFreeze => (1, 0),
+            FrozenState::Frozen(ticks) => (2, ticks),
+            FrozenState::Immune(ticks) => (3, ticks),
+        };
+        key.extend([
+            nylo.x.to_bits(),
+            nylo.z.to_bits(),
+            frozen,
+            ticks,
+            nylo.leaked as u32,
+            nylo.dead as u32,
+            nylo.hitpoints,
+            nylo.pending_damage.map_or(u32::MAX, |damage| damage),
+        ]);
+    }
+    key
+}
+
+impl Search {
+    fn visit(&mut self, mut wave: Wave, steps: &mut Vec<PlanStep>) {
+        while !wave.is_finished() && !wave.can_cast() {
+            wave.step();
+        }
+
+        self.nodes += 1;
+        if self.nodes > SEARCH_BUDGET {
+            return;
+        }
+
+        // Leaks and casts only ever go up, so this branch can't beat the best one
+        if let Some(best) = &self.best {
+            if (wave.leaks, steps.len()) > (best.leaks, best.steps.len()) {
+                return;
+            }
+        }
+
+        // The same state reached with no fewer casts plays out no better
+        let key = state_key(&wave);
+        match self.seen.get(&key) {
+            Some(casts) if *casts <= steps.len() => return,
+            _ => {
+                self.seen.insert(key, steps.len());
+            }
+        }
+
+        if wave.is_finished() {
+            let solution = Solution {
+                steps: steps.clone(),
+                leaks: wave.leaks,
+                ticks: wave.tick,
+                complete: true,
+            };
+            if self
+                .best
+                .as_ref()
+                .is_none_or(|best| solution.key() < best.key())
+            {
+                self.best = Some(solution);
+            }
+            return;
+        }
+
+        // Targets that catch the same set of nylos are interchangeable
+        let targets: Vec<usize> = wave.unfrozen().map(|(i, _)| i).collect();
+        let mut tried: Vec<Vec<usize>> = vec![];
+
+        for target in targets {
+            let caught = wave.barrage_targets(target);
+            if tried.contains(&caught) {
+                continue;
+            }
+
+            // Plans count on every barrage landing
+            let mut next = wave.clone();
+            next.cast(target, || BarrageHit::Hit(0));
+            steps.push(PlanStep {
+                tick: wave.tick,
+                target: wave.nylos[target].spawn,
+                x: wave.nylos[target].x,
+                z: wave.nylos[target].z,
+                caught: caught.iter().map(|i| wave.nylos[*i].spawn).collect(),
+            });
+            next.step();
+            self.visit(next, steps);
+            steps.pop();
+
+            tried.push(caught);
+        }
+
+        // Holding the cast for a tick can line up a bigger stack later
+        wave.step();
+        self.visit(wave, steps);
+    }
+}
+
+pub struct SolverPlugin;
+
+#[derive(Default)]
+pub struct CurrentSolution {
+    spawns: Vec<SpawnPoint>,
+    delays: SpawnDelays,
+    mode: Mode,
+    solution: Option<Result<Solution, String>>,
+    pub show_overlay: bool,
+}
+
+impl CurrentSolution {
+    // Whether the solution is for the wave being played, at the difficulty it's played at
+    fn is_for(&self, current_spawn: &CurrentSpawn, mode: Mode) -> bool {
+        self.spawns == current_spawn.spawns()
+            && self.delays == *current_spawn.delays()
+            && self.mode == mode
+    }
+}
+
+impl Plugin for SolverPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurrentSolution>()
+            .add_system(solver_ui)
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(draw_solution))
+            .add_system_set(SystemSet::on_update(GameState::Spawned).with_system(draw_solution))
+            .add_system_set(SystemSet::on_update(GameState::Paused).with_system(draw_solution));
+    }
+}
+
+fn solver_ui(
+    mut egui_context: ResMut<EguiContext>,
+    mut current_solution: ResMut<CurrentSolution>,
+    current_spawn: Res<CurrentSpawn>,
+    config: Res<Config>,
+) {
+    let spawns = current_spawn.spawns();
+
+    overlay::folded_window(egui_context.ctx_mut(), "Solver").show(egui_context.ctx_mut(), |ui| {
+        if spawns.is_empty() {
+            ui.label("Spawn or load a wave to solve it");
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("Solve").clicked() {
+                current_solution.spawns = spawns.to_vec();
+                current_solution.delays = current_spawn.delays().clone();
+                current_solution.mode = config.mode;
+                current_solution.solution =
+                    Some(solve(spawns, current_spawn.delays(), config.stacking));
+            }
+            ui.checkbox(&mut current_solution.show_overlay, "Overlay");
+        });
+
+        if !current_solution.is_for(&current_spawn, config.mode) {
+            return;
+        }
+
+        if let Some(Err(err)) = &current_solution.solution {
+            ui.colored_label(egui::Color32::RED, err);
+        }
+        if let Some(Ok(solution)) = &current_solution.solution {
+            ui.label(format!(
+                "{} casts, {} leaks, frozen by T{}",
+                solution.steps.len(),
+                solution.leaks,
+                solution.ticks
+            ));
+            if !solution.complete {
+                ui.label("Search budget ran out, so there may be a better plan");
+            }
+            let mut plan = solution
+                .steps
+                .iter()
+                .map(PlanStep::describe)
+                .collect::<Vec<_>>()
+                .join("\n");
+            ui.add(egui::TextEdit::multiline(&mut plan).desired_rows(4));
+        }
+    });
+}
+
+// Marks the 3x3 area of every upcoming cast, with the next one highlighted
+fn draw_solution(
+    mut egui_context: ResMut<EguiContext>,
+    current_solution: Res<CurrentSolution>,
+    current_spawn: Res<CurrentSpawn>,
+    config: Res<Config>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<camera::Camera>>,
+) {
+    let solution = match &current_solution.solution {
+        Some(Ok(solution))
+            if current_solution.show_overlay
+                && current_solution.is_for(&current_spawn, config.mode) =>
+        {
+            solution
+        }
+        _ => return,
+    };
+    let projector = match camera_query
+        .get_single()
+        .ok()
+        .and_then(|(camera, transform)| Projector::new(camera, transform))
+    {
+        Some(projector) => projector,
+        None => return,
+    };
+
+    let painter = overlay::painter(egui_context.ctx_mut(), "solution");
+    let upcoming = solution
+        .steps
+        .iter()
+        .filter(|step| step.tick >= current_spawn.tick());
+
+    for (i, step) in upcoming.enumerate() {
+        let color = if i == 0 {
+            egui::Color32::from_rgb(0, 220, 255)
+        } else {
+            egui::Color32::from_rgba_unmultiplied(0, 220, 255, 90)
+        };
+
+        if let Some(square) = projector.floor_square(step.x, step.z, 1.5) {
+            painter.add(egui::Shape::closed_line(
+                square,
+                egui::Stroke::new(2.0, color),
+            ));
+        }
+        if let Some(label) = projector.project(Vec3::new(step.x, 2.0, step.z)) {
+            painter.text(
+                label,
+                egui::Align2::CENTER_BOTTOM,
+                format!("T{} {}", step.tick, step.target.label()),
+                egui::FontId::proportional(14.0),
+                color,
+            );
+        }
+    }
+}
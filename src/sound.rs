@@ -0,0 +1,126 @@
+use crate::loading::AudioAssets;
+use crate::matomenos::{BarrageCast, CurrentSpawn, Matomenos, MoveNylos};
+use crate::overlay::folded_window;
+use crate::sim::FrozenState;
+use crate::tick::on_game_tick;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+pub struct SoundPlugin;
+
+pub struct SoundSettings {
+    pub enabled: bool,
+    pub volume: f32,
+    pub metronome: bool,
+    pub metronome_volume: f32,
+    pub cues: bool,
+}
+
+impl Default for SoundSettings {
+    fn default() -> SoundSettings {
+        SoundSettings {
+            enabled: true,
+            volume: 0.6,
+            metronome: false,
+            metronome_volume: 0.4,
+            cues: true,
+        }
+    }
+}
+
+impl SoundSettings {
+    fn play(&self, audio: &Audio, sound: &Handle<AudioSource>, volume: f32) {
+        if self.enabled {
+            audio.play_with_settings(
+                sound.clone(),
+                PlaybackSettings::ONCE.with_volume(self.volume * volume),
+            );
+        }
+    }
+}
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SoundSettings>()
+            .add_system(sound_ui)
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(on_game_tick)
+                    .with_system(play_metronome)
+                    .with_system(play_freeze_cue.before(MoveNylos)),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Spawned).with_system(play_wave_cues))
+            .add_system_set(SystemSet::on_update(GameState::Paused).with_system(play_wave_cues));
+    }
+}
+
+fn sound_ui(mut egui_context: ResMut<EguiContext>, mut settings: ResMut<SoundSettings>) {
+    folded_window(egui_context.ctx_mut(), "Sound").show(egui_context.ctx_mut(), |ui| {
+        ui.checkbox(&mut settings.enabled, "Sound");
+        ui.add(egui::Slider::new(&mut settings.volume, 0.0..=1.0).text("Volume"));
+        ui.checkbox(&mut settings.metronome, "Tick metronome");
+        ui.add(egui::Slider::new(&mut settings.metronome_volume, 0.0..=1.0).text("Metronome"));
+        ui.checkbox(&mut settings.cues, "Spawn, cast, freeze and leak cues");
+    });
+}
+
+fn play_metronome(
+    audio: Res<Audio>,
+    sounds: Res<AudioAssets>,
+    settings: Res<SoundSettings>,
+    state: Res<State<GameState>>,
+) {
+    let in_room = matches!(
+        state.current(),
+        GameState::Playing | GameState::Spawned | GameState::Paused
+    );
+    if settings.metronome && in_room {
+        settings.play(&audio, &sounds.tick, settings.metronome_volume);
+    }
+}
+
+// A freeze lands on the tick after the cast, once ShouldFreeze becomes Frozen
+fn play_freeze_cue(
+    audio: Res<Audio>,
+    sounds: Res<AudioAssets>,
+    settings: Res<SoundSettings>,
+    nylos_query: Query<&Matomenos>,
+) {
+    let landing = nylos_query
+        .iter()
+        .any(|nylo| nylo.frozen() == FrozenState::ShouldFreeze);
+    if settings.cues && landing {
+        settings.play(&audio, &sounds.freeze, 1.0);
+    }
+}
+
+fn play_wave_cues(
+    audio: Res<Audio>,
+    sounds: Res<AudioAssets>,
+    settings: Res<SoundSettings>,
+    current_spawn: Res<CurrentSpawn>,
+    mut casts: EventReader<BarrageCast>,
+    mut was_spawned: Local<bool>,
+    mut last_leaks: Local<i32>,
+) {
+    let cast = casts.iter().count() > 0;
+    let spawned = current_spawn.is_spawned() && !*was_spawned;
+    let leaked = current_spawn.leaks > *last_leaks;
+
+    *was_spawned = current_spawn.is_spawned();
+    *last_leaks = current_spawn.leaks;
+
+    if !settings.cues {
+        return;
+    }
+    if spawned {
+        settings.play(&audio, &sounds.spawn, 1.0);
+    }
+    if cast {
+        settings.play(&audio, &sounds.cast, 1.0);
+    }
+    if leaked {
+        settings.play(&audio, &sounds.leak, 1.0);
+    }
+}
@@ -1,24 +1,24 @@
-use rand::Rng;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct SpawnPoint {
     pub x: f32,
     pub y: f32,
 }
 
-const SPAWN_POINT_N1: SpawnPoint = SpawnPoint { x: 13.5, y: 1.5 };
-const SPAWN_POINT_N2: SpawnPoint = SpawnPoint { x: 17.5, y: 1.5 };
-const SPAWN_POINT_N3: SpawnPoint = SpawnPoint { x: 21.5, y: 1.5 };
-const SPAWN_POINT_N4: SpawnPoint = SpawnPoint { x: 25.5, y: 1.5 };
-const SPAWN_POINT_N4_WALL: SpawnPoint = SpawnPoint { x: 25.5, y: 3.5 };
+pub const SPAWN_POINT_N1: SpawnPoint = SpawnPoint { x: 13.5, y: 1.5 };
+pub const SPAWN_POINT_N2: SpawnPoint = SpawnPoint { x: 17.5, y: 1.5 };
+pub const SPAWN_POINT_N3: SpawnPoint = SpawnPoint { x: 21.5, y: 1.5 };
+pub const SPAWN_POINT_N4: SpawnPoint = SpawnPoint { x: 25.5, y: 1.5 };
+pub const SPAWN_POINT_N4_WALL: SpawnPoint = SpawnPoint { x: 25.5, y: 3.5 };
 
-const SPAWN_POINT_S1: SpawnPoint = SpawnPoint { x: 13.5, y: 21.5 };
-const SPAWN_POINT_S2: SpawnPoint = SpawnPoint { x: 17.5, y: 21.5 };
-const SPAWN_POINT_S3: SpawnPoint = SpawnPoint { x: 21.5, y: 21.5 };
-const SPAWN_POINT_S4: SpawnPoint = SpawnPoint { x: 25.5, y: 21.5 };
-const SPAWN_POINT_S4_WALL: SpawnPoint = SpawnPoint { x: 25.5, y: 19.5 };
+pub const SPAWN_POINT_S1: SpawnPoint = SpawnPoint { x: 13.5, y: 21.5 };
+pub const SPAWN_POINT_S2: SpawnPoint = SpawnPoint { x: 17.5, y: 21.5 };
+pub const SPAWN_POINT_S3: SpawnPoint = SpawnPoint { x: 21.5, y: 21.5 };
+pub const SPAWN_POINT_S4: SpawnPoint = SpawnPoint { x: 25.5, y: 21.5 };
+pub const SPAWN_POINT_S4_WALL: SpawnPoint = SpawnPoint { x: 25.5, y: 19.5 };
 
-const SPAWN_POINTS: &[SpawnPoint] = &[
+pub const SPAWN_POINTS: &[SpawnPoint] = &[
     SPAWN_POINT_N1,
     SPAWN_POINT_N2,
     SPAWN_POINT_N3,
@@ -31,19 +31,128 @@ const SPAWN_POINTS: &[SpawnPoint] = &[
     SPAWN_POINT_S4_WALL,
 ];
 
-const MAX_SPAWNS: usize = SPAWN_POINTS.len();
+// Chat notation for each entry in SPAWN_POINTS, in the same order
+const SPAWN_LABELS: &[&str] = &["N1", "N2", "N3", "N4", "N4w", "S1", "S2", "S3", "S4", "S4w"];
 
-pub fn generate_spawn_points(k: usize) -> Vec<SpawnPoint> {
-    let mut rng = rand::thread_rng();
+pub const MAX_SPAWNS: usize = SPAWN_POINTS.len();
 
+// Latest a spawn can come up after the rest of the wave, in ticks
+pub const MAX_SPAWN_DELAY: u32 = 10;
+
+impl SpawnPoint {
+    // Position in SPAWN_POINTS, or None for a point that isn't one of them
+    pub fn index(&self) -> Option<usize> {
+        SPAWN_POINTS.iter().position(|spawn| spawn == self)
+    }
+
+    pub fn label(&self) -> &'static str {
+        self.index().map_or("?", |i| SPAWN_LABELS[i])
+    }
+
+    pub fn from_label(label: &str) -> Option<SpawnPoint> {
+        SPAWN_LABELS
+            .iter()
+            .position(|l| l.eq_ignore_ascii_case(label))
+            .map(|i| SPAWN_POINTS[i])
+    }
+}
+
+// Spawns that come up some ticks after the rest of the wave, like scuffed spawns in the real fight
+// Ticks are kept in SPAWN_POINTS order, so equal delays compare equal however they were set.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SpawnDelays([u32; MAX_SPAWNS]);
+
+impl SpawnDelays {
+    pub fn get(&self, spawn: SpawnPoint) -> u32 {
+        spawn.index().map_or(0, |i| self.0[i])
+    }
+
+    pub fn set(&mut self, spawn: SpawnPoint, ticks: u32) {
+        if let Some(i) = spawn.index() {
+            self.0[i] = ticks;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|ticks| *ticks == 0)
+    }
+}
+
+// Parses chat notation such as "N1 N3 S2+1 S4w" into spawn points ordered like
+// SPAWN_POINTS, where "+1" means that spawn comes up a tick late
+pub fn parse_spawns(notation: &str) -> Result<(Vec<SpawnPoint>, SpawnDelays), String> {
+    let mut spawns = vec![];
+    let mut delays = SpawnDelays::default();
+
+    for entry in notation
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|entry| !entry.is_empty())
+    {
+        let (label, delay) = match entry.split_once('+') {
+            Some((label, delay)) => {
+                let delay = delay
+                    .parse()
+                    .ok()
+                    .filter(|delay| (1..=MAX_SPAWN_DELAY).contains(delay))
+                    .ok_or_else(|| format!("Invalid spawn delay \"{}\"", entry))?;
+                (label, delay)
+            }
+            None => (entry, 0),
+        };
+        let spawn =
+            SpawnPoint::from_label(label).ok_or_else(|| format!("Unknown spawn \"{}\"", label))?;
+        if spawns.contains(&spawn) {
+            return Err(format!("Duplicate spawn \"{}\"", label));
+        }
+        spawns.push(spawn);
+        delays.set(spawn, delay);
+    }
+
+    if spawns.is_empty() {
+        return Err("No spawns given".to_string());
+    }
+
+    spawns.sort_by_key(SpawnPoint::index);
+    Ok((spawns, delays))
+}
+
+pub fn format_spawns(spawns: &[SpawnPoint]) -> String {
+    spawns
+        .iter()
+        .map(SpawnPoint::label)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn format_delayed_spawns(spawns: &[SpawnPoint], delays: &SpawnDelays) -> String {
+    spawns
+        .iter()
+        .map(|spawn| match delays.get(*spawn) {
+            0 => spawn.label().to_string(),
+            delay => format!("{}+{}", spawn.label(), delay),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// The rng for the nth wave of a seed. Both numbers go into the rng seed
+// as they are, so no two seed and wave pairs share a wave.
+pub fn wave_rng(seed: u64, wave: u64) -> StdRng {
+    let mut bytes = [0u8; 32];
+    bytes[..8].copy_from_slice(&seed.to_le_bytes());
+    bytes[8..16].copy_from_slice(&wave.to_le_bytes());
+    StdRng::from_seed(bytes)
+}
+
+pub fn generate_spawn_points<R: Rng>(rng: &mut R, k: usize) -> Vec<SpawnPoint> {
     let mut spawns = vec![];
 
-    for i in 0..MAX_SPAWNS {
+    for (i, spawn) in SPAWN_POINTS.iter().enumerate().take(MAX_SPAWNS) {
         let rn = MAX_SPAWNS - i;
         let rk = k - spawns.len();
 
         if rng.gen_range(0..=MAX_SPAWNS) % rn < rk {
-            spawns.push(SPAWN_POINTS[i]);
+            spawns.push(*spawn);
             if spawns.len() >= k {
                 break;
             }
@@ -52,3 +161,101 @@ pub fn generate_spawn_points(k: usize) -> Vec<SpawnPoint> {
 
     spawns
 }
+
+// Makes each spawn come up to max_ticks late with the given chance
+pub fn scuff_spawns<R: Rng>(
+    rng: &mut R,
+    spawns: &[SpawnPoint],
+    chance: f64,
+    max_ticks: u32,
+) -> SpawnDelays {
+    let mut delays = SpawnDelays::default();
+
+    // Without scuffing the rng is left alone, so seeds keep giving the same waves
+    if chance <= 0.0 {
+        return delays;
+    }
+
+    for spawn in spawns {
+        if rng.gen_bool(chance) {
+            delays.set(*spawn, rng.gen_range(1..=max_ticks));
+        }
+    }
+
+    delays
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_notation_in_spawn_order() {
+        let (spawns, delays) = parse_spawns("S2+1, N3 n1  S4w").unwrap();
+        assert_eq!(
+            spawns,
+            vec![
+                SPAWN_POINT_N1,
+                SPAWN_POINT_N3,
+                SPAWN_POINT_S2,
+                SPAWN_POINT_S4_WALL
+            ]
+        );
+        assert_eq!(delays.get(SPAWN_POINT_S2), 1);
+        assert_eq!(delays.get(SPAWN_POINT_N1), 0);
+        assert_eq!(format_delayed_spawns(&spawns, &delays), "N1 N3 S2+1 S4w");
+    }
+
+    #[test]
+    fn formatted_notation_parses_back() {
+        let mut delays = SpawnDelays::default();
+        delays.set(SPAWN_POINT_N4_WALL, MAX_SPAWN_DELAY);
+        let spawns = vec![SPAWN_POINT_N2, SPAWN_POINT_N4_WALL, SPAWN_POINT_S1];
+
+        let notation = format_delayed_spawns(&spawns, &delays);
+        assert_eq!(parse_spawns(&notation), Ok((spawns, delays)));
+    }
+
+    #[test]
+    fn delays_compare_equal_whatever_order_they_were_set_in() {
+        let mut a = SpawnDelays::default();
+        a.set(SPAWN_POINT_N1, 1);
+        a.set(SPAWN_POINT_S2, 2);
+        let mut b = SpawnDelays::default();
+        b.set(SPAWN_POINT_S2, 2);
+        b.set(SPAWN_POINT_N3, 1);
+        b.set(SPAWN_POINT_N1, 1);
+        b.set(SPAWN_POINT_N3, 0);
+
+        assert_eq!(a, b);
+
+        b.set(SPAWN_POINT_N1, 0);
+        b.set(SPAWN_POINT_S2, 0);
+        assert_ne!(a, b);
+        assert!(b.is_empty());
+    }
+
+    #[test]
+    fn rejects_bad_notation() {
+        assert!(parse_spawns("").is_err());
+        assert!(parse_spawns("N1 N5").is_err());
+        assert!(parse_spawns("N1 n1").is_err());
+        assert!(parse_spawns("N1+0").is_err());
+        assert!(parse_spawns("N1+11").is_err());
+        assert!(parse_spawns("N1+x").is_err());
+    }
+
+    #[test]
+    fn neighbouring_seeds_give_different_waves() {
+        let wave = |seed, wave| generate_spawn_points(&mut wave_rng(seed, wave), 4);
+        assert_eq!(wave(1, 1), wave(1, 1));
+        assert!((0..20).any(|n| wave(1, n + 1) != wave(2, n)));
+    }
+
+    #[test]
+    fn points_off_the_table_have_no_index() {
+        assert_eq!(SPAWN_POINT_S4_WALL.index(), Some(MAX_SPAWNS - 1));
+        assert_eq!(SpawnPoint { x: 0.5, y: 0.5 }.index(), None);
+        assert_eq!(SpawnPoint { x: 0.5, y: 0.5 }.label(), "?");
+    }
+}
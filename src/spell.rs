@@ -21,6 +21,11 @@ impl Plugin for SpellPlugin {
                 SystemSet::on_update(GameState::Spawned)
                     .with_system(handle_spell_click)
                     .with_system(handle_spell_reset),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Paused)
+                    .with_system(handle_spell_click)
+                    .with_system(handle_spell_reset),
             );
     }
 }
@@ -74,9 +79,9 @@ fn handle_spell_click(
             Interaction::Clicked => {
                 spell.is_active = !spell.is_active;
                 if spell.is_active {
-                    image.0 = images.spell_selected_sprite.clone().into();
+                    image.0 = images.spell_selected_sprite.clone();
                 } else {
-                    image.0 = images.spell_sprite.clone().into();
+                    image.0 = images.spell_sprite.clone();
                 }
             }
             Interaction::Hovered | Interaction::None => {}
@@ -94,9 +99,9 @@ fn handle_spell_reset(
         let mut image = image_query.get_mut(*child).unwrap();
 
         if spell.is_active {
-            image.0 = images.spell_selected_sprite.clone().into();
+            image.0 = images.spell_selected_sprite.clone();
         } else {
-            image.0 = images.spell_sprite.clone().into();
+            image.0 = images.spell_sprite.clone();
         }
     };
 }
@@ -0,0 +1,127 @@
+use crate::GameState;
+use bevy::ecs::schedule::ShouldRun;
+use bevy::prelude::*;
+use bevy_egui::EguiContext;
+use std::time::Duration;
+
+pub struct TickPlugin;
+
+// Length of a game tick at normal speed
+pub const TICK_SECONDS: f32 = 0.6;
+
+pub const MIN_SPEED: f32 = 0.25;
+pub const MAX_SPEED: f32 = 2.0;
+
+// The single clock every tick-driven system runs on, so that pausing and
+// changing the speed affects all of them the same way
+pub struct GameTick {
+    timer: Timer,
+    ticked: bool,
+    pub speed: f32,
+    pub step: bool,
+}
+
+impl Default for GameTick {
+    fn default() -> GameTick {
+        GameTick {
+            timer: Timer::from_seconds(TICK_SECONDS, true),
+            ticked: false,
+            speed: 1.0,
+            step: false,
+        }
+    }
+}
+
+impl GameTick {
+    // Real time scaled by the game speed, for timers that count in game time
+    pub fn scale(&self, delta: Duration) -> Duration {
+        delta.mul_f32(self.speed)
+    }
+
+    // How far through the current tick the game is, from 0.0 to 1.0
+    pub fn percent(&self) -> f32 {
+        self.timer.percent()
+    }
+
+    // A clock that has just ticked, for running tick systems in tests
+    #[cfg(test)]
+    pub fn ticked() -> GameTick {
+        GameTick {
+            ticked: true,
+            ..Default::default()
+        }
+    }
+}
+
+impl Plugin for TickPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameTick>()
+            .add_system_to_stage(CoreStage::PreUpdate, advance_tick)
+            .add_system(pause_controls);
+    }
+}
+
+// Run criteria for systems that should run once per game tick
+pub fn on_game_tick(tick: Res<GameTick>) -> ShouldRun {
+    if tick.ticked {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+// Run criteria for systems that play out a wave once per game tick. The tick keeps
+// running in the room between waves, but nothing should move or count down there.
+pub fn on_wave_tick(tick: Res<GameTick>, state: Res<State<GameState>>) -> ShouldRun {
+    let in_wave = matches!(state.current(), GameState::Spawned | GameState::Paused);
+    if tick.ticked && in_wave {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+pub fn toggle_pause(state: &mut State<GameState>) {
+    match state.current() {
+        GameState::Spawned => state.push(GameState::Paused).unwrap(),
+        GameState::Paused => state.pop().unwrap(),
+        _ => {}
+    }
+}
+
+fn advance_tick(time: Res<Time>, state: Res<State<GameState>>, mut tick: ResMut<GameTick>) {
+    let ticked = match state.current() {
+        GameState::Paused => std::mem::take(&mut tick.step),
+        GameState::Playing | GameState::Spawned => {
+            let delta = tick.scale(time.delta());
+            tick.timer.tick(delta).just_finished()
+        }
+        _ => false,
+    };
+
+    // A single step while paused starts a fresh tick once play resumes
+    if ticked && *state.current() == GameState::Paused {
+        tick.timer.reset();
+    }
+
+    tick.ticked = ticked;
+}
+
+fn pause_controls(
+    mut egui_context: ResMut<EguiContext>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut state: ResMut<State<GameState>>,
+    mut tick: ResMut<GameTick>,
+) {
+    // Don't pause while typing a share code
+    if egui_context.ctx_mut().wants_keyboard_input() {
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::P) {
+        toggle_pause(&mut state);
+    } else if keyboard_input.just_pressed(KeyCode::Period) && *state.current() == GameState::Paused
+    {
+        tick.step = true;
+    }
+}